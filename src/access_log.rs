@@ -0,0 +1,60 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    date::common_log_date,
+    http::{HttpMethod, HttpVersion},
+};
+
+#[derive(Debug, Clone)]
+pub struct AccessLogEntry {
+    pub time: SystemTime,
+    pub method: HttpMethod,
+    pub path: String,
+    pub version: HttpVersion,
+    pub status_code: u16,
+    pub response_size: u64,
+    pub elapsed: Duration,
+}
+
+impl AccessLogEntry {
+    pub fn common_log_format(&self) -> String {
+        return format!(
+            "- - - [{}] \"{} {} {}\" {} {}",
+            common_log_date(self.time),
+            self.method.as_str(),
+            self.path,
+            self.version,
+            self.status_code,
+            self.response_size
+        );
+    }
+}
+
+pub fn common_log(entry: &AccessLogEntry) {
+    println!("{}", entry.common_log_format());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    #[test]
+    fn test_common_log_format() {
+        let entry = AccessLogEntry {
+            time: UNIX_EPOCH + Duration::from_secs(971186136),
+            method: HttpMethod::Get,
+            path: "/apache_pb.gif".to_string(),
+            version: HttpVersion::Http10,
+            status_code: 200,
+            response_size: 2326,
+            elapsed: Duration::from_millis(3),
+        };
+
+        assert_eq!(
+            entry.common_log_format(),
+            "- - - [10/Oct/2000:13:55:36 +0000] \"GET /apache_pb.gif HTTP/1.0\" 200 2326"
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use crate::{
+    http::{HttpRequest, HttpResponse},
+    router::Middleware,
+};
+
+#[derive(Debug, Clone)]
+pub struct BasicAuth {
+    credentials: Vec<u8>,
+    realm: String,
+}
+
+fn base64_value(byte: u8) -> Option<u32> {
+    let value = match byte {
+        b'A'..=b'Z' => byte - b'A',
+        b'a'..=b'z' => byte - b'a' + 26,
+        b'0'..=b'9' => byte - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return None,
+    };
+
+    return Some(value as u32);
+}
+
+fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    let bytes = encoded.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (i, group) in bytes.chunks(4).enumerate() {
+        let padding = group.iter().rev().take_while(|&&byte| byte == b'=').count();
+        let last = i == bytes.len() / 4 - 1;
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+
+        let mut value = 0;
+        for &byte in &group[..4 - padding] {
+            value = (value << 6) | base64_value(byte)?;
+        }
+        value <<= 6 * padding as u32;
+
+        let group_bytes = [(value >> 16) as u8, (value >> 8) as u8, value as u8];
+        decoded.extend_from_slice(&group_bytes[..3 - padding]);
+    }
+
+    return Some(decoded);
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut diff = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or_default();
+        let y = b.get(i).copied().unwrap_or_default();
+        diff |= (x ^ y) as usize;
+    }
+
+    return diff == 0;
+}
+
+impl BasicAuth {
+    pub fn new(user: &str, password: &str) -> BasicAuth {
+        BasicAuth {
+            credentials: format!("{}:{}", user, password).into_bytes(),
+            realm: "restricted".to_owned(),
+        }
+    }
+
+    pub fn set_realm(&mut self, realm: &str) -> &mut Self {
+        self.realm = realm.to_owned();
+        return self;
+    }
+
+    pub fn middleware(&self) -> Middleware {
+        let auth = Arc::new(self.clone());
+        Box::new(move |req, next| {
+            let auth = Arc::clone(&auth);
+            Box::pin(async move {
+                if auth.is_authorized(&req) {
+                    return next.run(req).await;
+                }
+
+                auth.challenge()
+            })
+        })
+    }
+
+    fn is_authorized(&self, req: &HttpRequest) -> bool {
+        let provided = req
+            .header("Authorization")
+            .and_then(|authorization| authorization.split_once(' '))
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Basic"))
+            .and_then(|(_, encoded)| base64_decode(encoded.trim()));
+
+        return match provided {
+            Some(provided) => constant_time_eq(&provided, &self.credentials),
+            None => false,
+        };
+    }
+
+    fn challenge(&self) -> HttpResponse {
+        let mut res = HttpResponse::unauthorized("authentication required");
+        let realm = self.realm.replace('\\', "\\\\").replace('"', "\\\"");
+        res.insert_header(
+            "WWW-Authenticate",
+            &format!("Basic realm=\"{}\", charset=\"UTF-8\"", realm),
+        );
+        return res;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{http::HttpMethod, router::Router};
+
+    fn auth_router() -> Router {
+        let mut auth = BasicAuth::new("admin", "hunter2");
+        auth.set_realm("admin area");
+
+        let mut router: Router = Router::new(None);
+        router.use_middleware(auth.middleware());
+        router.get(
+            "/admin",
+            Box::new(|_req| Box::pin(async move { HttpResponse::text("welcome") })),
+        );
+        return router;
+    }
+
+    fn auth_request(authorization: Option<&str>) -> HttpRequest {
+        let mut req = HttpRequest {
+            method: HttpMethod::Get,
+            path: "/admin".to_string(),
+            ..Default::default()
+        };
+
+        if let Some(authorization) = authorization {
+            req.headers.insert("Authorization", authorization);
+        }
+
+        return req;
+    }
+
+    #[test]
+    fn test_base64_decode() {
+        assert_eq!(
+            base64_decode("YWRtaW46aHVudGVyMg==").unwrap(),
+            b"admin:hunter2"
+        );
+        assert_eq!(base64_decode("Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(base64_decode("Zm9vYmE=").unwrap(), b"fooba");
+        assert_eq!(base64_decode("Zm9vYmFy").unwrap(), b"foobar");
+        assert_eq!(base64_decode("").unwrap(), b"");
+        assert!(base64_decode("Zm9v!mFy").is_none());
+        assert!(base64_decode("Zm9").is_none());
+        assert!(base64_decode("Zg==Zm9v").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_rejects_missing_credentials() {
+        let res = auth_router().fetch(auth_request(None)).await.unwrap();
+
+        assert_eq!(res.status_code, 401);
+        assert_eq!(
+            res.get_header("WWW-Authenticate"),
+            Some("Basic realm=\"admin area\", charset=\"UTF-8\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_rejects_wrong_password() {
+        let router = auth_router();
+
+        let wrong = auth_request(Some("Basic YWRtaW46d3Jvbmc="));
+        let res = router.fetch(wrong).await.unwrap();
+        assert_eq!(res.status_code, 401);
+
+        let bearer = auth_request(Some("Bearer YWRtaW46aHVudGVyMg=="));
+        let res = router.fetch(bearer).await.unwrap();
+        assert_eq!(res.status_code, 401);
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_accepts_correct_password() {
+        let req = auth_request(Some("Basic YWRtaW46aHVudGVyMg=="));
+        let res = auth_router().fetch(req).await.unwrap();
+
+        assert_eq!(res.status_code, 200);
+        assert_eq!(res.body, b"welcome");
+    }
+}
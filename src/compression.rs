@@ -0,0 +1,162 @@
+use std::io::Write;
+
+use flate2::{Compression, write::GzEncoder};
+
+use crate::{http::HttpResponse, router::Middleware};
+
+fn accepts_gzip(accept_encoding: &str) -> bool {
+    return accept_encoding.split(',').any(|encoding| {
+        let mut parts = encoding.split(';').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        if !name.eq_ignore_ascii_case("gzip") {
+            return false;
+        }
+
+        let quality = parts
+            .find_map(|param| param.strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        return quality > 0.0;
+    });
+}
+
+fn should_compress(response: &HttpResponse, threshold: usize) -> bool {
+    if !response.compression_enabled() || response.body.len() < threshold {
+        return false;
+    }
+
+    if matches!(response.status_code, 204 | 206 | 304) {
+        return false;
+    }
+
+    return !response.has_header("Content-Encoding");
+}
+
+fn gzip_bytes(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    return encoder.finish();
+}
+
+fn compress_response(accept_encoding: Option<&str>, response: &mut HttpResponse, threshold: usize) {
+    let accepted = accept_encoding.is_some_and(accepts_gzip);
+    if !accepted || !should_compress(response, threshold) {
+        return;
+    }
+
+    let Ok(compressed) = gzip_bytes(&response.body) else {
+        return;
+    };
+
+    response.remove_header("Content-Length");
+    response.insert_header("Content-Encoding", "gzip");
+    response.insert_header("Vary", "Accept-Encoding");
+    response.body = compressed;
+}
+
+pub fn gzip(threshold: usize) -> Middleware {
+    Box::new(move |req, next| {
+        Box::pin(async move {
+            let accept_encoding = req.header("Accept-Encoding").map(str::to_owned);
+
+            let mut res = next.run(req).await;
+            compress_response(accept_encoding.as_deref(), &mut res, threshold);
+            res
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+    use crate::{http::HttpRequest, router::Router};
+
+    fn gzip_request(path: &str, accept_encoding: Option<&str>) -> HttpRequest {
+        let mut req = HttpRequest {
+            path: path.to_string(),
+            ..Default::default()
+        };
+
+        if let Some(accept_encoding) = accept_encoding {
+            req.headers.insert("Accept-Encoding", accept_encoding);
+        }
+
+        return req;
+    }
+
+    fn gzip_router() -> Router {
+        let mut router: Router = Router::new(None);
+        router.use_middleware(gzip(64));
+        router.get(
+            "/large",
+            Box::new(|_req| Box::pin(async move { HttpResponse::text(&"hello ".repeat(100)) })),
+        );
+        router.get(
+            "/small",
+            Box::new(|_req| Box::pin(async move { HttpResponse::text("hello") })),
+        );
+        router.get(
+            "/image",
+            Box::new(|_req| {
+                Box::pin(async move {
+                    let mut res = HttpResponse::body(vec![0; 1024], Some("image/png"));
+                    res.set_compression(false);
+                    res
+                })
+            }),
+        );
+        return router;
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        assert!(accepts_gzip("gzip"));
+        assert!(accepts_gzip("deflate, GZIP;q=0.5"));
+        assert!(!accepts_gzip("gzip;q=0"));
+        assert!(!accepts_gzip("br, deflate"));
+    }
+
+    #[tokio::test]
+    async fn test_gzip_round_trip() {
+        let router = gzip_router();
+        let req = gzip_request("/large", Some("gzip, deflate"));
+        let mut res = router.fetch(req).await.unwrap();
+
+        let mut decoded = String::new();
+        GzDecoder::new(res.body.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "hello ".repeat(100));
+
+        let compressed_length = res.body.len();
+        let bytes = res.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.contains("Content-Encoding: gzip\r\n"));
+        assert!(response_str.contains(&format!("Content-Length: {}\r\n", compressed_length)));
+    }
+
+    #[tokio::test]
+    async fn test_gzip_skips_when_not_applicable() {
+        let router = gzip_router();
+
+        let res = router.fetch(gzip_request("/large", None)).await.unwrap();
+        assert_eq!(res.body, "hello ".repeat(100).as_bytes());
+
+        let res = router
+            .fetch(gzip_request("/small", Some("gzip")))
+            .await
+            .unwrap();
+        assert_eq!(res.body, b"hello");
+
+        let res = router
+            .fetch(gzip_request("/image", Some("gzip")))
+            .await
+            .unwrap();
+        assert_eq!(res.body, vec![0; 1024]);
+    }
+}
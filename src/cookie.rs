@@ -0,0 +1,187 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CookieError {
+    InvalidName(String),
+    InvalidAttribute { attribute: String, value: String },
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct CookieOptions {
+    pub path: Option<String>,
+    pub domain: Option<String>,
+    pub max_age: Option<i64>,
+    pub http_only: bool,
+    pub secure: bool,
+    pub same_site: Option<SameSite>,
+}
+
+impl fmt::Display for CookieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CookieError::InvalidName(name) => write!(f, "invalid cookie name {:?}", name),
+            CookieError::InvalidAttribute { attribute, value } => {
+                write!(f, "invalid cookie {} {:?}", attribute, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CookieError {}
+
+impl SameSite {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+}
+
+fn is_token(text: &str) -> bool {
+    return !text.is_empty()
+        && text
+            .bytes()
+            .all(|byte| byte.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&byte));
+}
+
+fn is_cookie_octet(byte: u8) -> bool {
+    return matches!(byte, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E);
+}
+
+fn encode_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if is_cookie_octet(byte) && byte != b'%' {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    return encoded;
+}
+
+fn checked_attribute<'a>(attribute: &str, value: &'a str) -> Result<&'a str, CookieError> {
+    if value
+        .chars()
+        .any(|c| c.is_control() || c == ';' || c == ',')
+    {
+        return Err(CookieError::InvalidAttribute {
+            attribute: attribute.to_owned(),
+            value: value.to_owned(),
+        });
+    }
+    return Ok(value);
+}
+
+pub(crate) fn format_set_cookie(
+    name: &str,
+    value: &str,
+    options: &CookieOptions,
+) -> Result<String, CookieError> {
+    if !is_token(name) {
+        return Err(CookieError::InvalidName(name.to_owned()));
+    }
+
+    let mut cookie = format!("{}={}", name, encode_value(value));
+
+    if let Some(path) = &options.path {
+        cookie += format!("; Path={}", checked_attribute("Path", path)?).as_str();
+    }
+
+    if let Some(domain) = &options.domain {
+        cookie += format!("; Domain={}", checked_attribute("Domain", domain)?).as_str();
+    }
+
+    if let Some(max_age) = options.max_age {
+        cookie += format!("; Max-Age={}", max_age).as_str();
+    }
+
+    if options.http_only {
+        cookie += "; HttpOnly";
+    }
+
+    if options.secure {
+        cookie += "; Secure";
+    }
+
+    if let Some(same_site) = options.same_site {
+        cookie += format!("; SameSite={}", same_site.as_str()).as_str();
+    }
+
+    return Ok(cookie);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_plain_cookie() {
+        let cookie = format_set_cookie("id", "42", &CookieOptions::default()).unwrap();
+        assert_eq!(cookie, "id=42");
+    }
+
+    #[test]
+    fn test_format_cookie_with_all_options() {
+        let options = CookieOptions {
+            path: Some("/".to_owned()),
+            domain: Some("example.com".to_owned()),
+            max_age: Some(3600),
+            http_only: true,
+            secure: true,
+            same_site: Some(SameSite::Lax),
+        };
+
+        let cookie = format_set_cookie("session", "abc", &options).unwrap();
+        assert_eq!(
+            cookie,
+            "session=abc; Path=/; Domain=example.com; Max-Age=3600; HttpOnly; Secure; SameSite=Lax"
+        );
+    }
+
+    #[test]
+    fn test_format_cookie_encodes_unsafe_values() {
+        let cookie = format_set_cookie(
+            "id",
+            "a\r\nSet-Cookie: admin=1; Path=/",
+            &CookieOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(cookie, "id=a%0D%0ASet-Cookie:%20admin=1%3B%20Path=/");
+
+        let cookie =
+            format_set_cookie("note", "50% \"off\", ok", &CookieOptions::default()).unwrap();
+        assert_eq!(cookie, "note=50%25%20%22off%22%2C%20ok");
+    }
+
+    #[test]
+    fn test_format_cookie_rejects_bad_name() {
+        let result = format_set_cookie("id\r\nX-Injected: 1", "42", &CookieOptions::default());
+        assert_eq!(
+            result,
+            Err(CookieError::InvalidName("id\r\nX-Injected: 1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_format_cookie_rejects_attribute_injection() {
+        let options = CookieOptions {
+            path: Some("/; Domain=evil.example".to_owned()),
+            ..Default::default()
+        };
+        let error = format_set_cookie("id", "42", &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid cookie Path \"/; Domain=evil.example\""
+        );
+    }
+}
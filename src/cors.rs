@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use crate::{
+    http::{HttpMethod, HttpRequest, HttpResponse},
+    router::Middleware,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AllowedOrigins {
+    Any,
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Cors {
+    allowed_origins: AllowedOrigins,
+    allowed_methods: Vec<HttpMethod>,
+    allowed_headers: Vec<String>,
+    max_age: Option<u64>,
+}
+
+impl Cors {
+    pub fn any() -> Cors {
+        return Cors::new(AllowedOrigins::Any);
+    }
+
+    pub fn origins(origins: &[&str]) -> Cors {
+        let origins = origins.iter().map(|origin| origin.to_string()).collect();
+        return Cors::new(AllowedOrigins::List(origins));
+    }
+
+    fn new(allowed_origins: AllowedOrigins) -> Cors {
+        Cors {
+            allowed_origins,
+            allowed_methods: vec![
+                HttpMethod::Get,
+                HttpMethod::Head,
+                HttpMethod::Post,
+                HttpMethod::Put,
+                HttpMethod::Delete,
+                HttpMethod::Patch,
+            ],
+            allowed_headers: Vec::new(),
+            max_age: None,
+        }
+    }
+
+    pub fn set_allowed_methods(&mut self, methods: &[HttpMethod]) -> &mut Self {
+        self.allowed_methods = methods.to_vec();
+        return self;
+    }
+
+    pub fn set_allowed_headers(&mut self, headers: &[&str]) -> &mut Self {
+        self.allowed_headers = headers.iter().map(|header| header.to_string()).collect();
+        return self;
+    }
+
+    pub fn set_max_age(&mut self, max_age: Option<u64>) -> &mut Self {
+        self.max_age = max_age;
+        return self;
+    }
+
+    pub fn middleware(&self) -> Middleware {
+        let cors = Arc::new(self.clone());
+        Box::new(move |req, next| {
+            let cors = Arc::clone(&cors);
+            Box::pin(async move {
+                let Some(origin) = cors.allowed_origin(&req) else {
+                    return next.run(req).await;
+                };
+
+                if cors.is_preflight(&req) {
+                    return cors.preflight_response(&req, &origin);
+                }
+
+                let mut res = next.run(req).await;
+                cors.insert_origin(&mut res, &origin);
+                res
+            })
+        })
+    }
+
+    fn allowed_origin(&self, req: &HttpRequest) -> Option<String> {
+        let origin = req.header("Origin")?;
+        return match &self.allowed_origins {
+            AllowedOrigins::Any => Some("*".to_owned()),
+            AllowedOrigins::List(origins) => origins
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+                .then(|| origin.to_owned()),
+        };
+    }
+
+    fn is_preflight(&self, req: &HttpRequest) -> bool {
+        return req.method == HttpMethod::Options
+            && req.header("Access-Control-Request-Method").is_some();
+    }
+
+    fn insert_origin(&self, res: &mut HttpResponse, origin: &str) {
+        res.insert_header("Access-Control-Allow-Origin", origin);
+        if self.allowed_origins != AllowedOrigins::Any {
+            res.append_header("Vary", "Origin");
+        }
+    }
+
+    fn preflight_response(&self, req: &HttpRequest, origin: &str) -> HttpResponse {
+        let mut res = HttpResponse::no_content();
+        self.insert_origin(&mut res, origin);
+
+        let methods = self
+            .allowed_methods
+            .iter()
+            .map(|method| method.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        res.insert_header("Access-Control-Allow-Methods", &methods);
+
+        let headers = if self.allowed_headers.is_empty() {
+            req.header("Access-Control-Request-Headers")
+                .unwrap_or_default()
+                .to_owned()
+        } else {
+            self.allowed_headers.join(", ")
+        };
+        if !headers.is_empty() {
+            res.insert_header("Access-Control-Allow-Headers", &headers);
+        }
+
+        if let Some(max_age) = self.max_age {
+            res.insert_header("Access-Control-Max-Age", &max_age.to_string());
+        }
+
+        return res;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::Router;
+
+    fn cors_router(cors: &Cors) -> Router {
+        let mut router: Router = Router::new(None);
+        router.use_middleware(cors.middleware());
+        router.get(
+            "/api/items",
+            Box::new(|_req| Box::pin(async move { HttpResponse::text("items") })),
+        );
+        return router;
+    }
+
+    fn origin_request(method: HttpMethod, origin: &str) -> HttpRequest {
+        let mut req = HttpRequest {
+            method,
+            path: "/api/items".to_string(),
+            ..Default::default()
+        };
+        req.headers.insert("Origin", origin);
+        return req;
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight() {
+        let mut cors = Cors::origins(&["https://app.example"]);
+        cors.set_allowed_methods(&[HttpMethod::Get, HttpMethod::Post])
+            .set_max_age(Some(600));
+        let router = cors_router(&cors);
+
+        let mut req = origin_request(HttpMethod::Options, "https://app.example");
+        req.headers.insert("Access-Control-Request-Method", "POST");
+        req.headers
+            .insert("Access-Control-Request-Headers", "Content-Type, X-Token");
+        let res = router.fetch(req).await.unwrap();
+
+        assert_eq!(res.status_code, 204);
+        assert_eq!(
+            res.get_header("Access-Control-Allow-Origin"),
+            Some("https://app.example")
+        );
+        assert_eq!(
+            res.get_header("Access-Control-Allow-Methods"),
+            Some("GET, POST")
+        );
+        assert_eq!(
+            res.get_header("Access-Control-Allow-Headers"),
+            Some("Content-Type, X-Token")
+        );
+        assert_eq!(res.get_header("Access-Control-Max-Age"), Some("600"));
+        assert_eq!(res.get_header("Vary"), Some("Origin"));
+    }
+
+    #[tokio::test]
+    async fn test_cors_simple_requests() {
+        let router = cors_router(&Cors::origins(&["https://app.example"]));
+
+        let res = router
+            .fetch(origin_request(HttpMethod::Get, "https://app.example"))
+            .await
+            .unwrap();
+        assert_eq!(res.body, b"items");
+        assert_eq!(
+            res.get_header("Access-Control-Allow-Origin"),
+            Some("https://app.example")
+        );
+
+        let res = router
+            .fetch(origin_request(HttpMethod::Get, "https://evil.example"))
+            .await
+            .unwrap();
+        assert_eq!(res.body, b"items");
+        assert!(!res.has_header("Access-Control-Allow-Origin"));
+
+        let router = cors_router(&Cors::any());
+        let res = router
+            .fetch(origin_request(HttpMethod::Get, "https://anyone.example"))
+            .await
+            .unwrap();
+        assert_eq!(res.get_header("Access-Control-Allow-Origin"), Some("*"));
+        assert!(!res.has_header("Vary"));
+    }
+}
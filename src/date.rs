@@ -0,0 +1,161 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+fn civil_from_days(days: i64) -> (i64, usize, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return (year, (month - 1) as usize, day);
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    return era * 146097 + day_of_era - 719468;
+}
+
+struct DateTime {
+    weekday: usize,
+    year: i64,
+    month: usize,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+}
+
+fn date_time(time: SystemTime) -> DateTime {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+
+    let days = seconds.div_euclid(86400);
+    let seconds_of_day = seconds.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+
+    return DateTime {
+        weekday: days.rem_euclid(7) as usize,
+        year,
+        month,
+        day,
+        hour: seconds_of_day / 3600,
+        minute: seconds_of_day % 3600 / 60,
+        second: seconds_of_day % 60,
+    };
+}
+
+pub fn http_date(time: SystemTime) -> String {
+    let date = date_time(time);
+    return format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[date.weekday],
+        date.day,
+        MONTHS[date.month],
+        date.year,
+        date.hour,
+        date.minute,
+        date.second
+    );
+}
+
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let parts = value.split_whitespace().collect::<Vec<_>>();
+    let [weekday, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+
+    if !WEEKDAYS.contains(&weekday.strip_suffix(',')?) {
+        return None;
+    }
+
+    let day = day.parse::<i64>().ok()?;
+    let month = MONTHS.iter().position(|name| name == month)? as i64 + 1;
+    let year = year.parse::<i64>().ok()?;
+
+    let mut clock = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (Some(Some(hour)), Some(Some(minute)), Some(Some(second)), None) =
+        (clock.next(), clock.next(), clock.next(), clock.next())
+    else {
+        return None;
+    };
+
+    if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 || year < 1970 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+    return Some(UNIX_EPOCH + Duration::from_secs(seconds as u64));
+}
+
+pub fn common_log_date(time: SystemTime) -> String {
+    let date = date_time(time);
+    return format!(
+        "{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000",
+        date.day, MONTHS[date.month], date.year, date.hour, date.minute, date.second
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_date_formats_rfc1123() {
+        let time = UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+
+        assert_eq!(http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1709251199);
+        assert_eq!(http_date(leap_day), "Thu, 29 Feb 2024 23:59:59 GMT");
+    }
+
+    #[test]
+    fn test_parse_http_date_round_trips() {
+        let time = UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(time));
+
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1709251199);
+        assert_eq!(parse_http_date(&http_date(leap_day)), Some(leap_day));
+        assert_eq!(parse_http_date(&http_date(UNIX_EPOCH)), Some(UNIX_EPOCH));
+    }
+
+    #[test]
+    fn test_parse_http_date_rejects_garbage() {
+        assert_eq!(parse_http_date("yesterday"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 PST"), None);
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 25:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49 GMT"), None);
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+    }
+
+    #[test]
+    fn test_common_log_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(971186136);
+        assert_eq!(common_log_date(time), "10/Oct/2000:13:55:36 +0000");
+    }
+}
@@ -0,0 +1,99 @@
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct HttpHeaders {
+    entries: Vec<(String, String)>,
+}
+
+impl HttpHeaders {
+    pub fn new() -> Self {
+        HttpHeaders::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn insert(&mut self, name: &str, value: &str) {
+        self.remove(name);
+        self.entries.push((name.to_owned(), value.to_owned()));
+    }
+
+    pub fn append(&mut self, name: &str, value: &str) {
+        self.entries.push((name.to_owned(), value.to_owned()));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_ignores_case() {
+        let mut headers = HttpHeaders::new();
+        headers.insert("Content-Length", "11");
+
+        assert_eq!(headers.get("content-length"), Some("11"));
+        assert_eq!(headers.get("CONTENT-LENGTH"), Some("11"));
+        assert!(headers.contains("Content-length"));
+    }
+
+    #[test]
+    fn test_insert_replaces_regardless_of_case() {
+        let mut headers = HttpHeaders::new();
+        headers.insert("Host", "a");
+        headers.insert("host", "b");
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.get("HOST"), Some("b"));
+
+        headers.remove("HoSt");
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn test_append_keeps_every_value_in_order() {
+        let mut headers = HttpHeaders::new();
+        headers.append("Accept", "text/html");
+        headers.append("accept", "application/json");
+
+        assert_eq!(headers.get("Accept"), Some("text/html"));
+        assert_eq!(
+            headers.get_all("ACCEPT"),
+            vec!["text/html", "application/json"]
+        );
+        assert!(headers.get_all("Missing").is_empty());
+    }
+}
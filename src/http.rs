@@ -1,8 +1,25 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt, io,
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, ready},
+    time::SystemTime,
+};
 
 use tokio::{
-    io::{AsyncBufReadExt, AsyncRead, BufReader},
-    net::TcpStream,
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf},
+    sync::mpsc,
+};
+
+use crate::{
+    cookie::{CookieError, CookieOptions, format_set_cookie},
+    date::http_date,
+    headers::HttpHeaders,
+    mime::{ContentType, content_type_for_extension},
 };
 
 #[derive(Default, Debug, PartialEq, Hash, Clone, Copy)]
@@ -19,31 +36,114 @@ pub enum HttpMethod {
     Patch,
 }
 
+#[derive(Default, Debug, PartialEq, Hash, Clone, Copy)]
+pub enum HttpVersion {
+    Http10,
+    #[default]
+    Http11,
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct HttpRequest {
     pub method: HttpMethod,
     pub path: String,
-    pub version: String,
-    pub headers: HashMap<String, String>,
+    pub version: HttpVersion,
+    pub headers: HttpHeaders,
     pub query_params: HashMap<String, Option<String>>,
+    pub query_pairs: Vec<(String, Option<String>)>,
     pub params: HashMap<String, String>,
     pub body: Vec<u8>,
+    pub peer_addr: Option<SocketAddr>,
+    pub matched_route: Option<String>,
+    pub(crate) raw: Vec<u8>,
+    pub(crate) body_stream: Option<BodyStream>,
+}
+
+pub(crate) type BodyReader = Box<dyn AsyncRead + Send + Unpin>;
+
+#[derive(Clone)]
+pub(crate) struct BodyStream {
+    reader: Arc<Mutex<Option<BodyReader>>>,
+    length: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BodyFraming {
+    Empty,
+    Length(usize),
+    Chunked,
+}
+
+pub(crate) type BodySender = mpsc::Sender<io::Result<Vec<u8>>>;
+
+struct ChannelReader {
+    receiver: mpsc::Receiver<io::Result<Vec<u8>>>,
+    pending: Vec<u8>,
+    offset: usize,
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct HttpResponse {
-    version: String,
-    status_code: u16,
+    version: HttpVersion,
+    pub(crate) status_code: u16,
     status_text: String,
-    headers: HashMap<String, String>,
+    headers: HttpHeaders,
+    cookies: Vec<String>,
+    skip_compression: bool,
+    body_stripped: bool,
+    stream: Option<BodyStream>,
     pub body: Vec<u8>,
 }
 
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub max_body_size: usize,
+    pub max_headers: usize,
+    pub max_header_bytes: usize,
+    pub max_line_length: usize,
+    pub keep_raw: bool,
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    EmptyRequest,
+    MalformedRequestLine(String),
+    UnknownMethod(String),
+    UnsupportedVersion(String),
+    InvalidEncoding(String),
+    BadHeader(String),
+    BadChunk(String),
+    IncompleteBody(String),
+    UnsupportedExpectation(String),
+    IncompleteHeaders,
+    UriTooLong,
+    BodyTooLarge,
+    HeadersTooLarge,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct InvalidStatus(pub u16);
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParamError {
+    Missing(String),
+    Invalid { name: String, value: String },
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct HttpResponseBuilder {
+    version: Option<HttpVersion>,
+    status: Option<(u16, String)>,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
 macro_rules! define_response_helpers {
     ($(($name:ident, $http_code:expr, $status_text:expr, $content_type:expr)),*) => {
         $(
             pub fn $name(t: &str) -> HttpResponse {
-                let mut response = HttpResponse::new("HTTP/1.1", $http_code, $status_text);
+                let mut response = HttpResponse::new(HttpVersion::Http11, $http_code, $status_text);
                 let bytes = t.as_bytes();
 
                 response.insert_header("Content-Type", $content_type);
@@ -54,80 +154,745 @@ macro_rules! define_response_helpers {
             }
         )*
     };
-    ($(($name:ident, $http_code:expr, $status_text:expr)),*) => {
+    ($(($name:ident, $http_code:expr)),*) => {
         $(
             pub fn $name() -> HttpResponse {
-                HttpResponse::new("HTTP/1.1", $http_code, $status_text)
+                HttpResponse::from_status($http_code)
             }
         )*
     };
 }
 
-fn parse_query_params(params: &str) -> Option<HashMap<String, Option<String>>> {
+pub fn canonical_reason(status_code: u16) -> &'static str {
+    match status_code {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        416 => "Range Not Satisfiable",
+        417 => "Expectation Failed",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        _ => "",
+    }
+}
+
+async fn read_bounded_line<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    line: &mut String,
+    limit: usize,
+) -> io::Result<Option<usize>> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+
+        let (length, done) = match available.iter().position(|byte| *byte == b'\n') {
+            Some(end) => (end + 1, true),
+            None => (available.len(), false),
+        };
+
+        if bytes.len() + length > limit {
+            return Ok(None);
+        }
+
+        bytes.extend_from_slice(&available[..length]);
+        reader.consume(length);
+
+        if done {
+            break;
+        }
+    }
+
+    let text = String::from_utf8(bytes)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "line is not valid utf-8"))?;
+    line.push_str(&text);
+
+    return Ok(Some(text.len()));
+}
+
+async fn read_chunk_line<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    line: &mut String,
+    options: &ParseOptions,
+) -> Result<usize, ParseError> {
+    return read_bounded_line(reader, line, options.max_line_length)
+        .await?
+        .ok_or_else(|| ParseError::BadChunk("chunk line too long".to_owned()));
+}
+
+pub(crate) async fn write_stream<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    mut body: BodyReader,
+    length: Option<u64>,
+) -> io::Result<u64> {
+    let mut buffer = vec![0; 8192];
+    let mut written = 0;
+
+    let Some(length) = length else {
+        loop {
+            let read = body.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+
+            written += read as u64;
+            writer
+                .write_all(format!("{:x}\r\n", read).as_bytes())
+                .await?;
+            writer.write_all(&buffer[..read]).await?;
+            writer.write_all(b"\r\n").await?;
+        }
+
+        writer.write_all(b"0\r\n\r\n").await?;
+        return Ok(written);
+    };
+
+    let mut body = body.take(length);
+
+    loop {
+        let read = body.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+
+        writer.write_all(&buffer[..read]).await?;
+        written += read as u64;
+    }
+
+    if written < length {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "response body ended before its content length",
+        ));
+    }
+
+    return Ok(written);
+}
+
+fn record(raw: &mut Option<&mut Vec<u8>>, bytes: &[u8]) {
+    if let Some(raw) = raw {
+        raw.extend_from_slice(bytes);
+    }
+}
+
+fn parse_chunk_size(line: &str) -> Result<usize, ParseError> {
+    let size = line.split(';').next().unwrap_or_default().trim();
+    return usize::from_str_radix(size, 16)
+        .map_err(|_| ParseError::BadChunk(format!("invalid chunk size {:?}", size)));
+}
+
+async fn forward_exact<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    sender: &BodySender,
+    mut remaining: usize,
+) -> Result<bool, ParseError> {
+    while remaining > 0 {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Err(ParseError::IncompleteBody("incomplete body".to_owned()));
+        }
+
+        let length = available.len().min(remaining);
+        let chunk = available[..length].to_vec();
+        reader.consume(length);
+        remaining -= length;
+
+        if sender.send(Ok(chunk)).await.is_err() {
+            return Ok(false);
+        }
+    }
+
+    return Ok(true);
+}
+
+async fn forward_body<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    framing: BodyFraming,
+    options: &ParseOptions,
+    sender: &BodySender,
+) -> Result<bool, ParseError> {
+    let mut total: usize = 0;
+
+    match framing {
+        BodyFraming::Empty => return Ok(true),
+        BodyFraming::Length(length) => return forward_exact(reader, sender, length).await,
+        BodyFraming::Chunked => loop {
+            let mut line = String::new();
+            if read_chunk_line(reader, &mut line, options).await? == 0 {
+                return Err(ParseError::IncompleteBody(
+                    "incomplete chunked body".to_owned(),
+                ));
+            }
+
+            let size = parse_chunk_size(&line)?;
+            if size == 0 {
+                break;
+            }
+
+            total = total
+                .checked_add(size)
+                .filter(|length| *length <= options.max_body_size)
+                .ok_or(ParseError::BodyTooLarge)?;
+
+            if !forward_exact(reader, sender, size).await? {
+                return Ok(false);
+            }
+
+            let mut terminator = String::new();
+            read_chunk_line(reader, &mut terminator, options).await?;
+            if !terminator.trim().is_empty() {
+                return Err(ParseError::IncompleteBody(
+                    "incomplete chunked body".to_owned(),
+                ));
+            }
+        },
+    }
+
+    loop {
+        let mut trailer = String::new();
+        let n = read_chunk_line(reader, &mut trailer, options).await?;
+        if n == 0 || trailer.trim().is_empty() {
+            return Ok(true);
+        }
+    }
+}
+
+pub(crate) async fn stream_body<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    framing: BodyFraming,
+    options: &ParseOptions,
+    sender: BodySender,
+) -> bool {
+    return match forward_body(reader, framing, options, &sender).await {
+        Ok(complete) => complete,
+        Err(e) => {
+            let e = match e {
+                ParseError::Io(e) => e,
+                e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+            };
+            sender.send(Err(e)).await.ok();
+            false
+        }
+    };
+}
+
+pub(crate) async fn read_chunked_body<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    options: &ParseOptions,
+    mut raw: Option<&mut Vec<u8>>,
+) -> Result<Vec<u8>, ParseError> {
+    let mut body = Vec::new();
+
+    loop {
+        let mut line = String::new();
+        if read_chunk_line(reader, &mut line, options).await? == 0 {
+            return Err(ParseError::IncompleteBody(
+                "incomplete chunked body".to_owned(),
+            ));
+        }
+        record(&mut raw, line.as_bytes());
+
+        let size = parse_chunk_size(&line)?;
+
+        if size == 0 {
+            break;
+        }
+
+        body.len()
+            .checked_add(size)
+            .filter(|length| *length <= options.max_body_size)
+            .ok_or(ParseError::BodyTooLarge)?;
+
+        let start = body.len();
+        let read = (&mut *reader)
+            .take(size as u64)
+            .read_to_end(&mut body)
+            .await?;
+        record(&mut raw, &body[start..]);
+
+        let mut terminator = String::new();
+        read_chunk_line(reader, &mut terminator, options).await?;
+        record(&mut raw, terminator.as_bytes());
+        if read < size || !terminator.trim().is_empty() {
+            return Err(ParseError::IncompleteBody(
+                "incomplete chunked body".to_owned(),
+            ));
+        }
+    }
+
+    loop {
+        let mut trailer = String::new();
+        let n = read_chunk_line(reader, &mut trailer, options).await?;
+        record(&mut raw, trailer.as_bytes());
+        if n == 0 || trailer.trim().is_empty() {
+            return Ok(body);
+        }
+    }
+}
+
+fn invalid_response(message: &str) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, message.to_owned());
+}
+
+pub(crate) async fn read_response<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    bodiless: bool,
+    options: &ParseOptions,
+) -> io::Result<HttpResponse> {
+    let mut status_line = String::new();
+    read_bounded_line(reader, &mut status_line, options.max_line_length)
+        .await?
+        .ok_or_else(|| invalid_response("status line too long"))?;
+    let mut parts = status_line.trim_end().splitn(3, ' ');
+    let version = parts
+        .next()
+        .and_then(HttpVersion::from)
+        .ok_or_else(|| invalid_response("invalid status line"))?;
+    let status_code = parts
+        .next()
+        .and_then(|code| code.parse::<u16>().ok())
+        .filter(|code| is_valid_status(*code))
+        .ok_or_else(|| invalid_response("invalid status code"))?;
+    let status_text = parts.next().unwrap_or_default();
+
+    let mut response = HttpResponse::new(version, status_code, status_text);
+    let (mut header_count, mut header_bytes) = (0, 0);
+    loop {
+        let mut line = String::new();
+        let n = read_bounded_line(reader, &mut line, options.max_line_length)
+            .await?
+            .ok_or_else(|| invalid_response("header line too long"))?;
+        if n == 0 {
+            return Err(invalid_response("response ended inside headers"));
+        }
+
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+
+        header_count += 1;
+        header_bytes += n;
+        if header_count > options.max_headers || header_bytes > options.max_header_bytes {
+            return Err(invalid_response("response headers too large"));
+        }
+
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| invalid_response("invalid header line"))?;
+        response.append_header(key.trim(), value.trim());
+    }
+
+    let chunked = response
+        .get_header("Transfer-Encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"));
+    let content_length = response
+        .get_header("Content-Length")
+        .map(|length| length.parse::<u64>())
+        .transpose()
+        .map_err(|_| invalid_response("invalid content length"))?;
+
+    if bodiless || matches!(status_code, 100..=199 | 204 | 304) {
+        return Ok(response);
+    }
+
+    let max_body_size = options.max_body_size;
+    if content_length.is_some_and(|length| length > max_body_size as u64) {
+        return Err(invalid_response("response body too large"));
+    }
+
+    response.body = if chunked {
+        read_chunked_body(reader, options, None)
+            .await
+            .map_err(|e| invalid_response(&e.to_string()))?
+    } else {
+        let mut body = Vec::new();
+        let limit = content_length.unwrap_or((max_body_size as u64).saturating_add(1));
+        reader.take(limit).read_to_end(&mut body).await?;
+        if body.len() > max_body_size {
+            return Err(invalid_response("response body too large"));
+        }
+        body
+    };
+
+    return Ok(response);
+}
+
+fn percent_decode(s: &str, plus_as_space: bool) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3)?;
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+
+                let hex = std::str::from_utf8(hex).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' if plus_as_space => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    return String::from_utf8(decoded).ok();
+}
+
+// An encoded slash would either split a segment once decoded or become
+// indistinguishable from a real separator, so such paths are rejected.
+fn decode_path(path: &str) -> Option<String> {
+    if path.to_ascii_lowercase().contains("%2f") {
+        return None;
+    }
+
+    return percent_decode(path, false);
+}
+
+fn parse_query_pairs(params: &str) -> Option<Vec<(String, Option<String>)>> {
     let params = params
         .split('&')
         .map(|s| s.split('=').collect::<Vec<_>>())
         .collect::<Vec<_>>();
 
-    let mut query_params_map: HashMap<String, Option<String>> = HashMap::default();
+    let mut query_pairs = Vec::with_capacity(params.len());
     for param in &params {
-        if param.len() == 0 || param.len() > 2 {
+        if param.is_empty() || param.len() > 2 {
             return None;
         }
 
         let value = if param.len() == 2 {
-            Some(param[1].trim().to_owned())
+            Some(percent_decode(param[1].trim(), true)?)
         } else {
             None
         };
 
-        query_params_map.insert(param[0].trim().to_owned(), value);
+        query_pairs.push((percent_decode(param[0].trim(), true)?, value));
     }
 
-    return Some(query_params_map);
+    return Some(query_pairs);
+}
+
+fn parse_cookies(header: &str, cookies: &mut HashMap<String, String>) {
+    for pair in header.split(';') {
+        let Some((name, value)) = pair.split_once('=') else {
+            continue;
+        };
+
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+
+        let value = value.trim();
+        let value = percent_decode(value, false).unwrap_or_else(|| value.to_owned());
+        cookies.insert(name.to_owned(), value);
+    }
+}
+
+fn is_valid_status(status_code: u16) -> bool {
+    return (100..=599).contains(&status_code);
+}
+
+fn validated_status(status_code: u16, status_text: &str) -> (u16, String) {
+    if !is_valid_status(status_code) {
+        return (500, canonical_reason(500).to_owned());
+    }
+
+    if status_text.is_empty() || status_text.chars().any(|c| c.is_control()) {
+        return (status_code, canonical_reason(status_code).to_owned());
+    }
+
+    return (status_code, status_text.to_owned());
 }
 
 impl Eq for HttpMethod {}
 
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_body_size: 2 * 1024 * 1024,
+            max_headers: 100,
+            max_header_bytes: 16 * 1024,
+            max_line_length: 8 * 1024,
+            keep_raw: false,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "{}", e),
+            ParseError::MalformedRequestLine(message)
+            | ParseError::UnknownMethod(message)
+            | ParseError::UnsupportedVersion(message)
+            | ParseError::InvalidEncoding(message)
+            | ParseError::BadHeader(message)
+            | ParseError::BadChunk(message)
+            | ParseError::IncompleteBody(message)
+            | ParseError::UnsupportedExpectation(message) => write!(f, "{}", message),
+            ParseError::EmptyRequest => write!(f, "connection closed before a request was sent"),
+            ParseError::IncompleteHeaders => {
+                write!(f, "connection closed before the end of the headers")
+            }
+            ParseError::UriTooLong => write!(f, "request uri too long"),
+            ParseError::BodyTooLarge => write!(f, "request body too large"),
+            ParseError::HeadersTooLarge => write!(f, "request header fields too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Missing(name) => write!(f, "missing route parameter '{}'", name),
+            ParamError::Invalid { name, value } => {
+                write!(
+                    f,
+                    "invalid value '{}' for route parameter '{}'",
+                    value, name
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+impl fmt::Display for InvalidStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid status code {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidStatus {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
 impl HttpResponse {
-    fn new(version: &str, status_code: u16, status_text: &str) -> HttpResponse {
+    fn new(version: HttpVersion, status_code: u16, status_text: &str) -> HttpResponse {
+        let (status_code, status_text) = validated_status(status_code, status_text);
         HttpResponse {
-            version: version.to_string(),
+            version,
             status_code,
-            status_text: status_text.to_string(),
-            headers: HashMap::default(),
+            status_text,
+            headers: HttpHeaders::new(),
+            cookies: Vec::default(),
+            skip_compression: false,
+            body_stripped: false,
+            stream: None,
             body: Vec::default(),
         }
     }
 
+    pub fn builder() -> HttpResponseBuilder {
+        HttpResponseBuilder::default()
+    }
+
     pub fn insert_header(&mut self, key: &str, value: &str) {
-        self.headers.insert(key.to_string(), value.to_string());
+        self.headers.insert(key, value);
+    }
+
+    pub fn append_header(&mut self, key: &str, value: &str) {
+        self.headers.append(key, value);
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        return self.headers.get(name);
+    }
+
+    pub fn has_header(&self, name: &str) -> bool {
+        return self.headers.contains(name);
+    }
+
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers.remove(name);
+    }
+
+    pub fn add_cookie(&mut self, name: &str, value: &str, options: &CookieOptions) {
+        if let Err(e) = self.try_add_cookie(name, value, options) {
+            panic!("{}", e);
+        }
+    }
+
+    pub fn try_add_cookie(
+        &mut self,
+        name: &str,
+        value: &str,
+        options: &CookieOptions,
+    ) -> Result<(), CookieError> {
+        self.cookies.push(format_set_cookie(name, value, options)?);
+        return Ok(());
+    }
+
+    pub fn status_code(&self) -> u16 {
+        return self.status_code;
+    }
+
+    pub fn status_text(&self) -> &str {
+        return &self.status_text;
+    }
+
+    pub fn set_status(&mut self, status_code: u16, status_text: &str) {
+        (self.status_code, self.status_text) = validated_status(status_code, status_text);
+    }
+
+    pub fn try_set_status(
+        &mut self,
+        status_code: u16,
+        status_text: &str,
+    ) -> Result<(), InvalidStatus> {
+        if !is_valid_status(status_code) {
+            return Err(InvalidStatus(status_code));
+        }
+
+        self.set_status(status_code, status_text);
+        return Ok(());
     }
 
     pub fn set_body(&mut self, body: &[u8]) {
         self.body = body.to_vec();
     }
 
-    pub fn get_bytes(&mut self) -> Vec<u8> {
+    pub fn set_compression(&mut self, compress: bool) {
+        self.skip_compression = !compress;
+    }
+
+    pub(crate) fn compression_enabled(&self) -> bool {
+        return !self.skip_compression && self.stream.is_none();
+    }
+
+    fn take_stream(&mut self) -> Option<(BodyReader, Option<u64>)> {
+        let stream = self.stream.take()?;
+        let reader = stream.reader.lock().ok()?.take()?;
+        return Some((reader, stream.length));
+    }
+
+    fn body_length(&self) -> Option<u64> {
+        return match &self.stream {
+            Some(stream) => stream.length,
+            None => Some(self.body.len() as u64),
+        };
+    }
+
+    pub(crate) fn strip_body(&mut self) {
+        self.body_stripped = true;
+        if self.body.is_empty() && self.stream.is_none() {
+            return;
+        }
+
+        match self.body_length() {
+            Some(length) => self.insert_header("Content-Length", &length.to_string()),
+            None => self.insert_header("Transfer-Encoding", "chunked"),
+        }
+
+        self.body.clear();
+        self.stream = None;
+    }
+
+    fn head_bytes(&mut self) -> Vec<u8> {
         let status_line = format!("{} {} {}", self.version, self.status_code, self.status_text);
-        let length = self.body.len();
+        let bodiless = matches!(self.status_code, 100..=199 | 204 | 304);
+        if bodiless {
+            self.remove_header("Content-Length");
+            self.remove_header("Transfer-Encoding");
+            self.body.clear();
+            self.stream = None;
+        } else if !self.body_stripped {
+            self.remove_header("Content-Length");
+        }
 
-        let mut response = format!("{status_line}\r\nContent-Length: {length}\r\n");
-        for (key, value) in &self.headers {
+        let framed =
+            bodiless || self.has_header("Content-Length") || self.has_header("Transfer-Encoding");
+
+        let mut response = format!("{status_line}\r\n");
+        match self.body_length() {
+            Some(length) if !framed => response += format!("Content-Length: {length}\r\n").as_str(),
+            None if !framed => response += "Transfer-Encoding: chunked\r\n",
+            _ => {}
+        }
+
+        if !self.has_header("Date") {
+            response += format!("Date: {}\r\n", http_date(SystemTime::now())).as_str();
+        }
+
+        for (key, value) in self.headers.iter() {
             response += format!("{}: {}\r\n", key, value).as_str();
         }
 
+        for cookie in &self.cookies {
+            response += format!("Set-Cookie: {}\r\n", cookie).as_str();
+        }
+
         response += "\r\n";
-        let mut response = response
-            .as_bytes()
-            .iter()
-            .map(|byte| *byte)
-            .collect::<Vec<_>>();
+        return response.into_bytes();
+    }
 
+    pub fn get_bytes(&mut self) -> Vec<u8> {
+        let mut response = self.head_bytes();
         response.append(&mut self.body);
         return response;
     }
 
+    pub async fn write_to<W: AsyncWrite + Unpin>(&mut self, writer: &mut W) -> io::Result<u64> {
+        let head = self.head_bytes();
+        writer.write_all(&head).await?;
+        writer.write_all(&self.body).await?;
+
+        let written = self.body.len() as u64;
+        self.body.clear();
+
+        return match self.take_stream() {
+            Some((body, length)) => write_stream(writer, body, length).await,
+            None => Ok(written),
+        };
+    }
+
     define_response_helpers!(
         (text, 200, "OK", "text/plain; charset=UTF-8"),
         (html, 200, "OK", "text/html; charset=UTF-8"),
@@ -149,16 +914,37 @@ impl HttpResponse {
         )
     );
 
-    define_response_helpers!((no_content, 204, "No Content"));
+    define_response_helpers!((ok, 200), (no_content, 204), (internal_server_error, 500));
 
-    pub fn redirect(url: &str) -> HttpResponse {
-        let mut res = HttpResponse::new("HTTP/1.1", 302, "Found");
-        res.insert_header("Location", url);
+    pub fn from_status(status_code: u16) -> HttpResponse {
+        HttpResponse::new(
+            HttpVersion::Http11,
+            status_code,
+            canonical_reason(status_code),
+        )
+    }
+
+    fn redirect_with_status(location: &str, status_code: u16) -> HttpResponse {
+        let mut res = HttpResponse::from_status(status_code);
+        res.insert_header("Location", location);
         return res;
     }
 
+    pub fn redirect(location: &str, permanent: bool) -> HttpResponse {
+        let status_code = if permanent { 301 } else { 302 };
+        return HttpResponse::redirect_with_status(location, status_code);
+    }
+
+    pub fn redirect_see_other(location: &str) -> HttpResponse {
+        return HttpResponse::redirect_with_status(location, 303);
+    }
+
+    pub fn redirect_temporary(location: &str) -> HttpResponse {
+        return HttpResponse::redirect_with_status(location, 307);
+    }
+
     pub fn body(bytes: Vec<u8>, content_type: Option<&str>) -> HttpResponse {
-        let mut res = HttpResponse::new("HTTP/1.1", 200, "OK");
+        let mut res = HttpResponse::new(HttpVersion::Http11, 200, "OK");
 
         if let Some(content_type) = content_type {
             res.insert_header("Content-Type", content_type);
@@ -167,13 +953,111 @@ impl HttpResponse {
         res.set_body(&bytes);
         return res;
     }
-}
 
-impl HttpMethod {
-    pub fn from(s: &str) -> Option<HttpMethod> {
-        match s {
-            "GET" => Some(HttpMethod::Get),
-            "HEAD" => Some(HttpMethod::Head),
+    pub fn from_reader<R: AsyncRead + Send + Unpin + 'static>(
+        reader: R,
+        length: u64,
+    ) -> HttpResponse {
+        let mut res = HttpResponse::new(HttpVersion::Http11, 200, "OK");
+        res.stream = Some(BodyStream {
+            reader: Arc::new(Mutex::new(Some(Box::new(reader)))),
+            length: Some(length),
+        });
+        return res;
+    }
+
+    pub fn chunked<R: AsyncRead + Send + Unpin + 'static>(reader: R) -> HttpResponse {
+        let mut res = HttpResponse::new(HttpVersion::Http11, 200, "OK");
+        res.stream = Some(BodyStream {
+            reader: Arc::new(Mutex::new(Some(Box::new(reader)))),
+            length: None,
+        });
+        return res;
+    }
+
+    pub async fn from_file(path: impl AsRef<Path>) -> io::Result<HttpResponse> {
+        let path = path.as_ref();
+        let contents = tokio::fs::read(path).await?;
+        let content_type = content_type_for_extension(&path.to_string_lossy());
+
+        return Ok(HttpResponse::body(contents, Some(content_type)));
+    }
+}
+
+impl AsyncRead for ChannelReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        while this.offset >= this.pending.len() {
+            match ready!(this.receiver.poll_recv(cx)) {
+                Some(Ok(chunk)) => {
+                    this.pending = chunk;
+                    this.offset = 0;
+                }
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+
+        let length = buf.remaining().min(this.pending.len() - this.offset);
+        buf.put_slice(&this.pending[this.offset..this.offset + length]);
+        this.offset += length;
+        return Poll::Ready(Ok(()));
+    }
+}
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyStream")
+            .field("length", &self.length)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HttpResponseBuilder {
+    pub fn version(mut self, version: HttpVersion) -> Self {
+        self.version = Some(version);
+        return self;
+    }
+
+    pub fn status(mut self, status_code: u16, status_text: &str) -> Self {
+        self.status = Some((status_code, status_text.to_owned()));
+        return self;
+    }
+
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_owned(), value.to_owned()));
+        return self;
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        return self;
+    }
+
+    pub fn build(self) -> HttpResponse {
+        let version = self.version.unwrap_or_default();
+        let (status_code, status_text) = self.status.unwrap_or((200, "OK".to_owned()));
+
+        let mut response = HttpResponse::new(version, status_code, &status_text);
+        for (key, value) in &self.headers {
+            response.insert_header(key, value);
+        }
+
+        response.body = self.body;
+        return response;
+    }
+}
+
+impl HttpMethod {
+    pub fn from(s: &str) -> Option<HttpMethod> {
+        match s {
+            "GET" => Some(HttpMethod::Get),
+            "HEAD" => Some(HttpMethod::Head),
             "POST" => Some(HttpMethod::Post),
             "PUT" => Some(HttpMethod::Put),
             "DELETE" => Some(HttpMethod::Delete),
@@ -184,153 +1068,1061 @@ impl HttpMethod {
             _ => None,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Connect => "CONNECT",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Trace => "TRACE",
+            HttpMethod::Patch => "PATCH",
+        }
+    }
+
+    pub fn all() -> [HttpMethod; 9] {
+        return [
+            HttpMethod::Get,
+            HttpMethod::Head,
+            HttpMethod::Post,
+            HttpMethod::Put,
+            HttpMethod::Delete,
+            HttpMethod::Connect,
+            HttpMethod::Options,
+            HttpMethod::Trace,
+            HttpMethod::Patch,
+        ];
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for HttpMethod {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return HttpMethod::from(s)
+            .ok_or_else(|| ParseError::UnknownMethod(format!("unknown method {:?}", s)));
+    }
+}
+
+impl HttpVersion {
+    pub fn from(s: &str) -> Option<HttpVersion> {
+        match s {
+            "HTTP/1.0" => Some(HttpVersion::Http10),
+            "HTTP/1.1" => Some(HttpVersion::Http11),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpVersion::Http10 => "HTTP/1.0",
+            HttpVersion::Http11 => "HTTP/1.1",
+        }
+    }
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
 
 impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)
+    }
+
+    pub fn header_all(&self, name: &str) -> Vec<&str> {
+        self.headers.get_all(name)
+    }
+
+    pub fn param_str(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|value| value.as_str())
+    }
+
+    pub fn param<T: FromStr>(&self, name: &str) -> Result<T, ParamError> {
+        let value = self
+            .param_str(name)
+            .ok_or_else(|| ParamError::Missing(name.to_owned()))?;
+
+        return value.parse().map_err(|_| ParamError::Invalid {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        });
+    }
+
+    pub fn query_str(&self, name: &str) -> Option<&str> {
+        return self
+            .query_params
+            .get(name)
+            .map(|value| value.as_deref().unwrap_or_default());
+    }
+
+    pub fn query_all(&self, name: &str) -> Vec<&str> {
+        return self
+            .query_pairs
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.as_deref().unwrap_or_default())
+            .collect();
+    }
+
+    pub fn query<T: FromStr>(&self, name: &str) -> Option<Result<T, T::Err>> {
+        return self.query_str(name).map(str::parse);
+    }
+
+    pub fn query_or<T: FromStr>(&self, name: &str, default: T) -> T {
+        return match self.query(name) {
+            Some(Ok(value)) => value,
+            _ => default,
+        };
+    }
+
+    pub fn cookies(&self) -> HashMap<String, String> {
+        let mut cookies = HashMap::default();
+        for header in self.header_all("Cookie") {
+            parse_cookies(header, &mut cookies);
+        }
+
+        return cookies;
+    }
+
+    pub fn text(&self) -> Result<String, std::str::Utf8Error> {
+        return std::str::from_utf8(&self.body).map(str::to_owned);
+    }
+
+    pub fn text_lossy(&self) -> String {
+        return String::from_utf8_lossy(&self.body).into_owned();
+    }
+
+    pub fn content_type(&self) -> Option<ContentType> {
+        return ContentType::parse(self.header("Content-Type")?);
+    }
+
+    pub fn form(&self) -> Option<HashMap<String, String>> {
+        if !self.content_type()?.is("application/x-www-form-urlencoded") {
+            return None;
+        }
+
+        let body = std::str::from_utf8(&self.body).ok()?;
+        if body.is_empty() {
+            return Some(HashMap::default());
+        }
+
+        let fields = parse_query_pairs(body)?
+            .into_iter()
+            .map(|(key, value)| (key, value.unwrap_or_default()))
+            .collect();
+
+        return Some(fields);
+    }
+
     pub async fn parse<R: AsyncRead + Unpin>(
         reader: &mut BufReader<R>,
-    ) -> Result<HttpRequest, Box<dyn std::error::Error>> {
+    ) -> Result<HttpRequest, ParseError> {
+        return HttpRequest::parse_with_options(reader, &ParseOptions::default()).await;
+    }
+
+    pub async fn parse_with_options<R: AsyncRead + Unpin>(
+        reader: &mut BufReader<R>,
+        options: &ParseOptions,
+    ) -> Result<HttpRequest, ParseError> {
+        let mut request = HttpRequest::parse_head(reader, options).await?;
+        request.read_body(reader, options).await?;
+        return Ok(request);
+    }
+
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        let forwarded = self
+            .header("X-Forwarded-For")
+            .and_then(|forwarded| forwarded.split(',').next())
+            .and_then(|client| client.trim().parse::<IpAddr>().ok());
+
+        return forwarded.or(self.peer_addr.map(|addr| addr.ip()));
+    }
+
+    pub fn wants_keep_alive(&self) -> bool {
+        let has_token = |token: &str| {
+            self.header_all("Connection")
+                .into_iter()
+                .flat_map(|value| value.split(','))
+                .any(|value| value.trim().eq_ignore_ascii_case(token))
+        };
+
+        if has_token("close") {
+            return false;
+        }
+
+        return self.version == HttpVersion::Http11 || has_token("keep-alive");
+    }
+
+    pub fn raw(&self) -> &[u8] {
+        return &self.raw;
+    }
+
+    pub fn is_asterisk_form(&self) -> bool {
+        return self.path == "*";
+    }
+
+    pub fn expects_continue(&self) -> bool {
+        return self.version == HttpVersion::Http11
+            && self
+                .header("Expect")
+                .is_some_and(|expect| expect.eq_ignore_ascii_case("100-continue"));
+    }
+
+    pub async fn parse_head<R: AsyncRead + Unpin>(
+        reader: &mut BufReader<R>,
+        options: &ParseOptions,
+    ) -> Result<HttpRequest, ParseError> {
         let mut line = String::new();
-        let n = reader.read_line(&mut line).await?;
+        while line.trim().is_empty() {
+            line.clear();
+            let n = read_bounded_line(reader, &mut line, options.max_line_length)
+                .await?
+                .ok_or(ParseError::UriTooLong)?;
 
-        if n == 0 {
-            return Ok(HttpRequest::default());
+            if n == 0 {
+                return Err(ParseError::EmptyRequest);
+            }
+        }
+
+        let mut raw = Vec::new();
+        if options.keep_raw {
+            raw.extend_from_slice(line.as_bytes());
+        }
+
+        let request_line = line.trim().split(' ').collect::<Vec<_>>();
+        if request_line.len() != 3 {
+            return Err(ParseError::MalformedRequestLine(
+                "request line must be made up of 3 components".to_owned(),
+            ));
+        }
+
+        let method = request_line[0].parse::<HttpMethod>()?;
+
+        if request_line[1] == "*" && method != HttpMethod::Options {
+            return Err(ParseError::MalformedRequestLine(format!(
+                "{} does not accept an asterisk request target",
+                method
+            )));
+        }
+
+        let uri = request_line[1].split('?').collect::<Vec<_>>();
+        if uri.len() > 2 || uri.is_empty() || (uri[0] == "*" && uri.len() > 1) {
+            return Err(ParseError::MalformedRequestLine(format!(
+                "invalid uri {:?}",
+                request_line[1]
+            )));
+        }
+
+        let path = decode_path(uri[0])
+            .ok_or_else(|| ParseError::InvalidEncoding("invalid path encoding".to_owned()))?;
+        let query_pairs = if uri.len() == 2 {
+            parse_query_pairs(uri[1])
+                .ok_or_else(|| ParseError::InvalidEncoding("invalid query string".to_owned()))?
+        } else {
+            Vec::new()
+        };
+        let query_params = query_pairs.iter().cloned().collect();
+
+        let version = HttpVersion::from(request_line[2]).ok_or_else(|| {
+            ParseError::UnsupportedVersion(format!(
+                "unsupported http version {:?}",
+                request_line[2]
+            ))
+        })?;
+        let mut headers = HttpHeaders::new();
+        let mut header_bytes = 0;
+
+        loop {
+            let mut line = String::new();
+            let n = read_bounded_line(reader, &mut line, options.max_line_length)
+                .await?
+                .ok_or_else(|| ParseError::BadHeader("header line too long".to_owned()))?;
+
+            if n == 0 {
+                return Err(ParseError::IncompleteHeaders);
+            }
+
+            if options.keep_raw {
+                raw.extend_from_slice(line.as_bytes());
+            }
+
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+
+            header_bytes += n;
+            if headers.len() >= options.max_headers || header_bytes > options.max_header_bytes {
+                return Err(ParseError::HeadersTooLarge);
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .filter(|(key, _)| !key.trim().is_empty())
+                .ok_or_else(|| ParseError::BadHeader(format!("invalid header line {:?}", line)))?;
+
+            headers.append(key.trim(), value.trim());
+        }
+
+        return Ok(HttpRequest {
+            method,
+            path,
+            version,
+            headers,
+            query_params,
+            query_pairs,
+            params: HashMap::default(),
+            body: Vec::new(),
+            peer_addr: None,
+            matched_route: None,
+            raw,
+            body_stream: None,
+        });
+    }
+
+    pub(crate) fn body_framing(&self, options: &ParseOptions) -> Result<BodyFraming, ParseError> {
+        let encodings = self.header_all("Transfer-Encoding");
+        if !encodings.is_empty() {
+            let last = encodings
+                .iter()
+                .flat_map(|encoding| encoding.split(','))
+                .map(str::trim)
+                .rfind(|coding| !coding.is_empty());
+
+            return match last {
+                Some(coding) if coding.eq_ignore_ascii_case("chunked") => Ok(BodyFraming::Chunked),
+                _ => Err(ParseError::BadHeader(
+                    "transfer-encoding must end with chunked".to_owned(),
+                )),
+            };
+        }
+
+        let mut lengths = self
+            .header_all("Content-Length")
+            .into_iter()
+            .flat_map(|length| length.split(','))
+            .map(|length| length.trim().parse::<usize>());
+
+        let Some(content_length) = lengths.next() else {
+            return Ok(BodyFraming::Empty);
+        };
+
+        let content_length = content_length
+            .map_err(|_| ParseError::BadHeader("invalid content length".to_owned()))?;
+        if lengths.any(|length| length != Ok(content_length)) {
+            return Err(ParseError::BadHeader(
+                "conflicting content lengths".to_owned(),
+            ));
+        }
+
+        if content_length > options.max_body_size {
+            return Err(ParseError::BodyTooLarge);
+        }
+
+        return match content_length {
+            0 => Ok(BodyFraming::Empty),
+            length => Ok(BodyFraming::Length(length)),
+        };
+    }
+
+    pub(crate) fn attach_body_stream(&mut self, framing: BodyFraming) -> BodySender {
+        let (sender, receiver) = mpsc::channel(8);
+        let reader = ChannelReader {
+            receiver,
+            pending: Vec::new(),
+            offset: 0,
+        };
+
+        self.body_stream = Some(BodyStream {
+            reader: Arc::new(Mutex::new(Some(Box::new(reader)))),
+            length: match framing {
+                BodyFraming::Length(length) => Some(length as u64),
+                _ => None,
+            },
+        });
+        return sender;
+    }
+
+    pub fn body_reader(&self) -> Option<Box<dyn AsyncRead + Send + Unpin>> {
+        return self.body_stream.as_ref()?.reader.lock().ok()?.take();
+    }
+
+    pub async fn read_body<R: AsyncRead + Unpin>(
+        &mut self,
+        reader: &mut BufReader<R>,
+        options: &ParseOptions,
+    ) -> Result<(), ParseError> {
+        match self.body_framing(options)? {
+            BodyFraming::Empty => {}
+            BodyFraming::Chunked => {
+                let raw = options.keep_raw.then_some(&mut self.raw);
+                self.body = read_chunked_body(reader, options, raw).await?;
+            }
+            BodyFraming::Length(content_length) => {
+                self.body.reserve(content_length);
+                let read = reader
+                    .take(content_length as u64)
+                    .read_to_end(&mut self.body)
+                    .await?;
+
+                if read < content_length {
+                    return Err(ParseError::IncompleteBody(format!(
+                        "incomplete body: expected {} bytes, got {}",
+                        content_length, read
+                    )));
+                }
+
+                if options.keep_raw {
+                    self.raw.extend_from_slice(&self.body);
+                }
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_http_request_parse_simple_get() {
+        let input = [
+            "GET /index.html HTTP/1.1",
+            "Host: 127.0.0.1:7878",
+            "Connection: keep-alive",
+            "",
+            "",
+        ]
+        .join("\r\n");
+
+        let mut reader = BufReader::new(std::io::Cursor::new(input));
+
+        let result = HttpRequest::parse(&mut reader)
+            .await
+            .expect("Should successfully parse GET");
+
+        assert!(matches!(result.method, HttpMethod::Get));
+        assert_eq!(result.path, "/index.html");
+        assert_eq!(result.version, HttpVersion::Http11);
+        assert_eq!(result.headers.get("Host").unwrap(), "127.0.0.1:7878");
+        assert!(result.body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_post_with_body() {
+        let input = [
+            "POST /api/save HTTP/1.1",
+            "Content-Type: text/plain",
+            "Content-Length: 11",
+            "",
+            "hello world",
+        ]
+        .join("\r\n");
+
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader)
+            .await
+            .expect("Should successfully parse POST");
+
+        assert!(matches!(result.method, HttpMethod::Post));
+        assert_eq!(result.path, "/api/save");
+        assert_eq!(result.headers.get("Content-Type").unwrap(), "text/plain");
+        assert_eq!(result.body, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_binary_body() {
+        let payload: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\r\n\r\nend\0";
+        let mut input = format!(
+            "POST /upload HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            payload.len()
+        )
+        .into_bytes();
+        input.extend_from_slice(payload);
+
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader)
+            .await
+            .expect("Should successfully parse binary POST");
+
+        assert_eq!(result.body, payload);
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_rejects_oversized_body() {
+        let input = "POST /upload HTTP/1.1\r\nContent-Length: 10737418240\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+
+        let options = ParseOptions {
+            max_body_size: 1024,
+            ..Default::default()
+        };
+        let result = HttpRequest::parse_with_options(&mut reader, &options).await;
+        assert!(matches!(result, Err(ParseError::BodyTooLarge)));
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_rejects_truncated_body() {
+        let input = "POST /upload HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello";
+        let mut reader = BufReader::new(Cursor::new(input));
+
+        match HttpRequest::parse(&mut reader).await {
+            Err(ParseError::IncompleteBody(message)) => {
+                assert!(message.contains("incomplete body"))
+            }
+            other => panic!("expected incomplete body error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_client_ip_prefers_forwarded_for() {
+        let mut request = HttpRequest {
+            peer_addr: Some("10.0.0.2:5000".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(request.client_ip(), Some("10.0.0.2".parse().unwrap()));
+
+        request
+            .headers
+            .insert("X-Forwarded-For", "203.0.113.7, 10.0.0.1");
+        assert_eq!(request.client_ip(), Some("203.0.113.7".parse().unwrap()));
+
+        request.headers.insert("X-Forwarded-For", "garbage");
+        assert_eq!(request.client_ip(), Some("10.0.0.2".parse().unwrap()));
+
+        assert_eq!(HttpRequest::default().client_ip(), None);
+    }
+
+    #[tokio::test]
+    async fn test_wants_keep_alive_defaults_by_version() {
+        let cases = [
+            ("HTTP/1.1", None, true),
+            ("HTTP/1.1", Some("close"), false),
+            ("HTTP/1.1", Some("Keep-Alive"), true),
+            ("HTTP/1.0", None, false),
+            ("HTTP/1.0", Some("keep-alive"), true),
+            ("HTTP/1.0", Some("keep-alive, close"), false),
+        ];
+
+        for (version, connection, expected) in cases {
+            let header = connection
+                .map(|value| format!("Connection: {}\r\n", value))
+                .unwrap_or_default();
+            let input = format!("GET / {}\r\n{}\r\n", version, header);
+            let mut reader = BufReader::new(Cursor::new(input));
+            let request = HttpRequest::parse(&mut reader).await.unwrap();
+            assert_eq!(
+                request.wants_keep_alive(),
+                expected,
+                "{version} {connection:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_reports_incomplete_headers() {
+        for input in [
+            "GET /cut HTTP/1.1\r\n",
+            "GET /cut HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n",
+            "GET /cut HTTP/1.1\r\nHost: exam",
+        ] {
+            let mut reader = BufReader::new(Cursor::new(input));
+            let result = HttpRequest::parse(&mut reader).await;
+            assert!(
+                matches!(result, Err(ParseError::IncompleteHeaders)),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_reports_empty_requests() {
+        for input in ["", "\r\n", "\r\n  \r\n"] {
+            let mut reader = BufReader::new(Cursor::new(input));
+            let result = HttpRequest::parse(&mut reader).await;
+            assert!(matches!(result, Err(ParseError::EmptyRequest)), "{input:?}");
+        }
+
+        let mut reader = BufReader::new(Cursor::new("\r\nGET /after HTTP/1.1\r\n\r\n"));
+        let result = HttpRequest::parse(&mut reader).await.unwrap();
+        assert_eq!(result.path, "/after");
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_classifies_request_line_errors() {
+        let cases = [
+            "GET /\r\n\r\n",
+            "GET /a?b?c HTTP/1.1\r\n\r\n",
+            "GET  / HTTP/1.1\r\n\r\n",
+        ];
+
+        for input in cases {
+            let mut reader = BufReader::new(Cursor::new(input));
+            let result = HttpRequest::parse(&mut reader).await;
+            assert!(
+                matches!(result, Err(ParseError::MalformedRequestLine(_))),
+                "{input:?}: {result:?}"
+            );
+        }
+
+        let mut reader = BufReader::new(Cursor::new("BREW /pot HTTP/1.1\r\n\r\n"));
+        match HttpRequest::parse(&mut reader).await {
+            Err(ParseError::UnknownMethod(message)) => assert!(message.contains("BREW")),
+            other => panic!("expected unknown method error, got {:?}", other),
+        }
+
+        let input = "POST / HTTP/1.1\r\nContent-Length: ten\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader).await;
+        assert!(matches!(result, Err(ParseError::BadHeader(_))));
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_validates_version() {
+        for (token, version) in [
+            ("HTTP/1.0", HttpVersion::Http10),
+            ("HTTP/1.1", HttpVersion::Http11),
+        ] {
+            let input = format!("GET / {}\r\n\r\n", token);
+            let mut reader = BufReader::new(Cursor::new(input));
+            let result = HttpRequest::parse(&mut reader).await.unwrap();
+            assert_eq!(result.version, version);
+        }
+
+        for token in ["GARBAGE", "HTTP/2", "HTTP/2.0", "http/1.1", "HTTP/1.2"] {
+            let input = format!("GET / {}\r\n\r\n", token);
+            let mut reader = BufReader::new(Cursor::new(input));
+
+            match HttpRequest::parse(&mut reader).await {
+                Err(ParseError::UnsupportedVersion(message)) => assert!(message.contains(token)),
+                other => panic!("expected version error for {}, got {:?}", token, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_request_keeps_raw_bytes_when_asked() {
+        let options = ParseOptions {
+            keep_raw: true,
+            ..Default::default()
+        };
+
+        let input = "POST /upload?x=%20 HTTP/1.1\r\nHost: example.com\r\nX-Odd:  spaced \r\nContent-Length: 5\r\n\r\nhello";
+        let mut reader =
+            BufReader::new(Cursor::new(format!("\r\n{}GET / HTTP/1.1\r\n\r\n", input)));
+        let request = HttpRequest::parse_with_options(&mut reader, &options)
+            .await
+            .unwrap();
+        assert_eq!(request.raw(), input.as_bytes());
+
+        let input = "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5;ext=1\r\nhello\r\n6\r\n world\r\n0\r\nX-Sum: 1\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let request = HttpRequest::parse_with_options(&mut reader, &options)
+            .await
+            .unwrap();
+        assert_eq!(request.body, b"hello world");
+        assert_eq!(request.raw(), input.as_bytes());
+
+        let mut reader = BufReader::new(Cursor::new(input));
+        let request = HttpRequest::parse(&mut reader).await.unwrap();
+        assert!(request.raw().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_asterisk_form() {
+        let mut reader = BufReader::new(Cursor::new("OPTIONS * HTTP/1.1\r\nHost: a\r\n\r\n"));
+        let request = HttpRequest::parse(&mut reader).await.unwrap();
+        assert_eq!(request.method, HttpMethod::Options);
+        assert!(request.is_asterisk_form());
+
+        for input in ["GET * HTTP/1.1\r\n\r\n", "OPTIONS *?a=1 HTTP/1.1\r\n\r\n"] {
+            let mut reader = BufReader::new(Cursor::new(input));
+            assert!(matches!(
+                HttpRequest::parse(&mut reader).await,
+                Err(ParseError::MalformedRequestLine(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_http_method_round_trips() {
+        for method in HttpMethod::all() {
+            assert_eq!(HttpMethod::from(method.as_str()), Some(method));
+            assert_eq!(method.to_string().parse::<HttpMethod>().unwrap(), method);
+        }
+
+        assert_eq!(HttpMethod::all().len(), 9);
+        assert_eq!(HttpMethod::Delete.to_string(), "DELETE");
+        assert!(matches!(
+            "get".parse::<HttpMethod>(),
+            Err(ParseError::UnknownMethod(_))
+        ));
+    }
+
+    #[test]
+    fn test_http_version_round_trips() {
+        for version in [HttpVersion::Http10, HttpVersion::Http11] {
+            assert_eq!(HttpVersion::from(&version.to_string()), Some(version));
+        }
+
+        assert_eq!(HttpVersion::default().to_string(), "HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_chunked_body() {
+        let input = [
+            "POST /upload HTTP/1.1",
+            "Transfer-Encoding: chunked",
+            "",
+            "5;name=value",
+            "hello",
+            "7",
+            ", world",
+            "0",
+            "X-Checksum: abc",
+            "",
+            "GET /next HTTP/1.1",
+        ]
+        .join("\r\n");
+
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader)
+            .await
+            .expect("Should successfully parse chunked POST");
+        assert_eq!(result.body, b"hello, world");
+
+        let mut next = String::new();
+        reader.read_line(&mut next).await.unwrap();
+        assert_eq!(next, "GET /next HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_rejects_bad_chunks() {
+        let input =
+            "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\nhello\r\n0\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader).await;
+        assert!(matches!(result, Err(ParseError::BadChunk(_))), "{input:?}");
+
+        let inputs = [
+            "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel",
+            "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nhello\r\n0\r\n\r\n",
+        ];
+
+        for input in inputs {
+            let mut reader = BufReader::new(Cursor::new(input));
+            let result = HttpRequest::parse(&mut reader).await;
+            assert!(
+                matches!(result, Err(ParseError::IncompleteBody(_))),
+                "{input:?}"
+            );
+        }
+
+        let input =
+            "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let options = ParseOptions {
+            max_body_size: 4,
+            ..Default::default()
+        };
+        let result = HttpRequest::parse_with_options(&mut reader, &options).await;
+        assert!(matches!(result, Err(ParseError::BodyTooLarge)));
+
+        let input = format!(
+            "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n{:x}\r\nworld",
+            usize::MAX
+        );
+        let mut reader = BufReader::new(Cursor::new(input));
+        let options = ParseOptions {
+            max_body_size: usize::MAX,
+            ..Default::default()
+        };
+        let result = HttpRequest::parse_with_options(&mut reader, &options).await;
+        assert!(matches!(result, Err(ParseError::BodyTooLarge)));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_rejects_overflowing_chunk_sizes() {
+        let input = format!("5\r\nhello\r\n{:x}\r\nworld", usize::MAX);
+        let mut reader = BufReader::new(Cursor::new(input));
+        let options = ParseOptions {
+            max_body_size: usize::MAX,
+            ..Default::default()
+        };
+
+        let mut request = HttpRequest::default();
+        let sender = request.attach_body_stream(BodyFraming::Chunked);
+        let result = forward_body(&mut reader, BodyFraming::Chunked, &options, &sender).await;
+        assert!(matches!(result, Err(ParseError::BodyTooLarge)));
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_rejects_ambiguous_framing() {
+        let inputs = [
+            (
+                "POST /upload HTTP/1.1\r\nTransfer-Encoding: gzip\r\nContent-Length: 5\r\n\r\nhello",
+                "transfer-encoding must end with chunked",
+            ),
+            (
+                "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked, gzip\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+                "transfer-encoding must end with chunked",
+            ),
+            (
+                "POST /upload HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\nhello!",
+                "conflicting content lengths",
+            ),
+            (
+                "POST /upload HTTP/1.1\r\nContent-Length: 5, 6\r\n\r\nhello!",
+                "conflicting content lengths",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let mut reader = BufReader::new(Cursor::new(input));
+            match HttpRequest::parse(&mut reader).await {
+                Err(ParseError::BadHeader(message)) => assert_eq!(message, expected, "{input:?}"),
+                other => panic!("expected bad header for {input:?}, got {:?}", other),
+            }
+        }
+
+        let input =
+            "POST /upload HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 5, 5\r\n\r\nhello";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let request = HttpRequest::parse(&mut reader).await.unwrap();
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_keeps_colons_in_header_values() {
+        let input = [
+            "GET / HTTP/1.1",
+            "Host: example.com:8080",
+            "Date: Mon, 01 Jan 2024 00:00:00 GMT",
+            "",
+            "",
+        ]
+        .join("\r\n");
+
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader).await.unwrap();
+
+        assert_eq!(result.header("Host"), Some("example.com:8080"));
+        assert_eq!(result.header("Date"), Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_reports_malformed_header_line() {
+        for line in ["NoColonHere", ": no-name"] {
+            let input = format!("GET / HTTP/1.1\r\n{}\r\n\r\n", line);
+            let mut reader = BufReader::new(Cursor::new(input));
+
+            match HttpRequest::parse(&mut reader).await {
+                Err(ParseError::BadHeader(message)) => assert!(message.contains(line)),
+                other => panic!("expected malformed header error, got {:?}", other),
+            }
         }
+    }
 
-        let request_line = line.trim().split(' ').collect::<Vec<_>>();
-        if request_line.len() != 3 {
-            return Err("request line must be made up of 3 components".into());
-        }
+    #[tokio::test]
+    async fn test_typed_query_accessors() {
+        let input = "GET /items?page=3&debug=true&verbose&size=big HTTP/1.1\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let req = HttpRequest::parse(&mut reader).await.unwrap();
 
-        let method = HttpMethod::from(request_line[0]).ok_or("invalid method".to_owned())?;
+        assert_eq!(req.query::<u32>("page"), Some(Ok(3)));
+        assert_eq!(req.query::<bool>("debug"), Some(Ok(true)));
+        assert!(matches!(req.query::<u32>("size"), Some(Err(_))));
+        assert_eq!(req.query::<u32>("missing"), None);
 
-        let uri = request_line[1].split('?').collect::<Vec<_>>();
-        if uri.len() > 2 || uri.len() == 0 {
-            return Err(format!("Invalid uri {}", request_line[1]).into());
-        }
+        assert_eq!(req.query_str("verbose"), Some(""));
+        assert_eq!(req.query::<String>("verbose"), Some(Ok(String::new())));
 
-        let path = uri[0].to_string();
-        let query_params = if uri.len() == 2 {
-            parse_query_params(uri[1]).ok_or("invalid query params")?
-        } else {
-            HashMap::default()
-        };
+        assert_eq!(req.query_or("page", 1), 3);
+        assert_eq!(req.query_or("size", 20), 20);
+        assert_eq!(req.query_or("missing", 1), 1);
+    }
 
-        let version = request_line[2].to_owned();
-        let mut headers = HashMap::<String, String>::default();
+    #[tokio::test]
+    async fn test_query_all_keeps_repeated_values_in_order() {
+        let input = "GET /posts?tag=rust&page=2&tag=http&tag=async HTTP/1.1\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let req = HttpRequest::parse(&mut reader).await.unwrap();
 
-        loop {
-            let mut line = String::new();
-            let n = reader.read_line(&mut line).await?;
+        assert_eq!(req.query_all("tag"), vec!["rust", "http", "async"]);
+        assert_eq!(req.query_all("page"), vec!["2"]);
+        assert!(req.query_all("missing").is_empty());
+        assert_eq!(req.query_str("tag"), Some("async"));
+    }
 
-            if n == 0 {
-                return Ok(HttpRequest::default());
-            }
+    #[test]
+    fn test_request_text_accessors() {
+        let mut req = HttpRequest {
+            body: "héllo".as_bytes().to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(req.text().unwrap(), "héllo");
+        assert_eq!(req.text_lossy(), "héllo");
 
-            let line = line.trim();
-            if line.is_empty() {
-                break;
-            }
+        req.body = b"bad \xff byte".to_vec();
+        assert!(req.text().is_err());
+        assert_eq!(req.text_lossy(), "bad \u{FFFD} byte");
+    }
 
-            let header_seperator = line.find(':').ok_or("invalid header".to_owned())?;
-            let (key, value) = line
-                .split_at_checked(header_seperator + 1)
-                .ok_or("invalid header".to_owned())?;
+    fn form_request(content_type: &str, body: &str) -> HttpRequest {
+        let mut req = HttpRequest {
+            body: body.as_bytes().to_vec(),
+            ..Default::default()
+        };
+        req.headers.insert("Content-Type", content_type);
+        return req;
+    }
 
-            let mut key = key.to_owned();
-            key.pop().ok_or("invalid header".to_owned())?;
+    #[test]
+    fn test_form_parses_urlencoded_body() {
+        let req = form_request(
+            "application/x-www-form-urlencoded; charset=UTF-8",
+            "name=Alan+Fayz&city=S%C3%A3o+Paulo&empty=&flag&tag=a&tag=b",
+        );
 
-            headers.insert(key.trim().to_string(), value.to_owned().trim().to_string());
-        }
+        let form = req.form().unwrap();
+        assert_eq!(form.get("name").unwrap(), "Alan Fayz");
+        assert_eq!(form.get("city").unwrap(), "São Paulo");
+        assert_eq!(form.get("empty").unwrap(), "");
+        assert_eq!(form.get("flag").unwrap(), "");
+        assert_eq!(form.get("tag").unwrap(), "b");
+    }
 
-        let mut body: Vec<u8> = Vec::new();
+    #[test]
+    fn test_request_content_type() {
+        let req = form_request("application/json; charset=utf-8", "{}");
+        let content_type = req.content_type().unwrap();
+        assert!(content_type.is("application/json"));
+        assert_eq!(content_type.param("charset"), Some("utf-8"));
 
-        if let Some(content_length) = headers.get("Content-Length") {
-            let content_length: usize = content_length.parse()?;
+        let req = form_request("multipart/form-data; boundary=XyZ", "");
+        assert_eq!(req.content_type().unwrap().param("boundary"), Some("XyZ"));
 
-            let mut line = String::new();
-            reader.read_line(&mut line).await?;
+        assert!(HttpRequest::default().content_type().is_none());
+    }
 
-            loop {
-                if body.len() >= content_length {
-                    break;
-                }
+    #[test]
+    fn test_form_requires_form_content_type() {
+        assert!(form_request("application/json", "a=1").form().is_none());
+        assert!(
+            form_request("application/x-www-form-urlencoded", "a=%zz")
+                .form()
+                .is_none()
+        );
+        assert!(
+            form_request("application/x-www-form-urlencoded", "")
+                .form()
+                .unwrap()
+                .is_empty()
+        );
+    }
 
-                let mut line = String::new();
-                let n = reader.read_line(&mut line).await?;
+    #[tokio::test]
+    async fn test_http_request_parse_enforces_header_limits() {
+        let options = ParseOptions {
+            max_headers: 3,
+            max_header_bytes: 64,
+            ..Default::default()
+        };
 
-                if n == 0 {
-                    break;
-                }
+        let input = "GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse_with_options(&mut reader, &options).await;
+        assert_eq!(result.unwrap().headers.len(), 3);
 
-                let mut bytes = line.as_bytes().iter().map(|byte| *byte).collect::<Vec<_>>();
-                body.append(&mut bytes);
-            }
-        }
+        let input = "GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\nD: 4\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse_with_options(&mut reader, &options).await;
+        assert!(matches!(result, Err(ParseError::HeadersTooLarge)));
 
-        return Ok(HttpRequest {
-            method,
-            path,
-            version,
-            headers,
-            query_params,
-            params: HashMap::default(),
-            body,
-        });
+        let input = format!("GET / HTTP/1.1\r\nX-Big: {}\r\n\r\n", "a".repeat(64));
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse_with_options(&mut reader, &options).await;
+        assert!(matches!(result, Err(ParseError::HeadersTooLarge)));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::io::Cursor;
+    #[tokio::test]
+    async fn test_http_request_parse_bounds_line_length() {
+        let input = "G".repeat(1024 * 1024);
+        let mut reader = BufReader::new(Cursor::new(input));
 
-    use super::*;
+        let result = HttpRequest::parse(&mut reader).await;
+        assert!(matches!(result, Err(ParseError::UriTooLong)));
+        assert!(reader.buffer().len() < 1024 * 1024);
+
+        let input = format!("GET / HTTP/1.1\r\nX-Long: {}\r\n\r\n", "a".repeat(9000));
+        let mut reader = BufReader::new(Cursor::new(input));
+
+        match HttpRequest::parse(&mut reader).await {
+            Err(ParseError::BadHeader(message)) => assert_eq!(message, "header line too long"),
+            other => panic!("expected line length error, got {:?}", other),
+        }
+    }
 
     #[tokio::test]
-    async fn test_http_request_parse_simple_get() {
-        let input = vec![
-            "GET /index.html HTTP/1.1",
-            "Host: 127.0.0.1:7878",
-            "Connection: keep-alive",
-            "",
+    async fn test_http_request_parse_mixed_case_headers() {
+        let input = [
+            "POST /api/save HTTP/1.1",
+            "content-length: 5",
+            "X-CUSTOM-Header: yes",
             "",
+            "hello",
         ]
         .join("\r\n");
 
-        let mut reader = BufReader::new(std::io::Cursor::new(input));
-
+        let mut reader = BufReader::new(Cursor::new(input));
         let result = HttpRequest::parse(&mut reader)
             .await
-            .expect("Should successfully parse GET");
+            .expect("Should parse lowercase content-length");
 
-        assert!(matches!(result.method, HttpMethod::Get));
-        assert_eq!(result.path, "/index.html");
-        assert_eq!(result.version, "HTTP/1.1");
-        assert_eq!(result.headers.get("Host").unwrap(), "127.0.0.1:7878");
-        assert!(result.body.is_empty());
+        assert_eq!(result.body, b"hello");
+        assert_eq!(result.header("Content-Length"), Some("5"));
+        assert_eq!(result.header("x-custom-header"), Some("yes"));
     }
 
     #[tokio::test]
-    async fn test_http_request_parse_post_with_body() {
-        let input = vec![
-            "POST /api/save HTTP/1.1",
-            "Content-Type: text/plain",
-            "Content-Length: 11",
+    async fn test_http_request_parse_repeated_headers() {
+        let input = [
+            "GET / HTTP/1.1",
+            "Accept: text/html",
+            "Cookie: a=1",
+            "accept: application/json",
             "",
             "",
-            "hello world",
         ]
         .join("\r\n");
 
         let mut reader = BufReader::new(Cursor::new(input));
         let result = HttpRequest::parse(&mut reader)
             .await
-            .expect("Should successfully parse POST");
+            .expect("Should parse repeated headers");
 
-        assert!(matches!(result.method, HttpMethod::Post));
-        assert_eq!(result.path, "/api/save");
-        assert_eq!(result.headers.get("Content-Type").unwrap(), "text/plain");
-        assert_eq!(result.body, b"hello world");
+        assert_eq!(result.header("Accept"), Some("text/html"));
+        assert_eq!(
+            result.header_all("Accept"),
+            vec!["text/html", "application/json"]
+        );
+        assert_eq!(result.header_all("Cookie"), vec!["a=1"]);
     }
 
     #[tokio::test]
@@ -378,7 +2170,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_parse_complex_query_params() {
-        let input = vec![
+        let input = [
             "GET /search?query=rust&verbose&mode= HTTP/1.1",
             "Host: localhost",
             "",
@@ -403,9 +2195,118 @@ mod tests {
         assert_eq!(result.query_params.len(), 3);
     }
 
+    #[test]
+    fn test_query_params_percent_decoding() {
+        let params: HashMap<_, _> = parse_query_pairs("path=a%2Fb&q=hello+world&caf%C3%A9=%41")
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        assert_eq!(params.get("path").unwrap(), &Some("a/b".to_string()));
+        assert_eq!(params.get("q").unwrap(), &Some("hello world".to_string()));
+        assert_eq!(params.get("café").unwrap(), &Some("A".to_string()));
+    }
+
+    #[test]
+    fn test_query_params_invalid_escapes() {
+        assert!(parse_query_pairs("q=100%").is_none());
+        assert!(parse_query_pairs("q=%G1").is_none());
+        assert!(parse_query_pairs("%4=x").is_none());
+        assert!(parse_query_pairs("q=%+1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_decodes_path() {
+        let input = "GET /my%20file.txt HTTP/1.1\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader).await.unwrap();
+        assert_eq!(result.path, "/my file.txt");
+
+        let input = "GET /caf%C3%A9/%E2%9C%93+x HTTP/1.1\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        let result = HttpRequest::parse(&mut reader).await.unwrap();
+        assert_eq!(result.path, "/café/✓+x");
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_rejects_encoded_slash() {
+        let input = "GET /a%2Fb HTTP/1.1\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        assert!(HttpRequest::parse(&mut reader).await.is_err());
+
+        let input = "GET /a%zz HTTP/1.1\r\n\r\n";
+        let mut reader = BufReader::new(Cursor::new(input));
+        assert!(HttpRequest::parse(&mut reader).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_request_parse_reports_invalid_encoding() {
+        let cases = [
+            ("GET /a%ZZ HTTP/1.1\r\n\r\n", "invalid path encoding"),
+            ("GET /a?q=%ZZ HTTP/1.1\r\n\r\n", "invalid query string"),
+        ];
+
+        for (input, expected) in cases {
+            let mut reader = BufReader::new(Cursor::new(input));
+            match HttpRequest::parse(&mut reader).await {
+                Err(ParseError::InvalidEncoding(message)) => assert_eq!(message, expected),
+                other => panic!("expected invalid encoding error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_typed_param_extraction() {
+        let mut request = HttpRequest::default();
+        request.params.insert("id".to_string(), "42".to_string());
+        request
+            .params
+            .insert("slug".to_string(), "hello".to_string());
+
+        assert_eq!(request.param::<u64>("id"), Ok(42));
+        assert_eq!(request.param_str("slug"), Some("hello"));
+        assert_eq!(request.param_str("missing"), None);
+
+        assert_eq!(
+            request.param::<u64>("slug"),
+            Err(ParamError::Invalid {
+                name: "slug".to_string(),
+                value: "hello".to_string()
+            })
+        );
+        assert_eq!(
+            request.param::<u64>("missing"),
+            Err(ParamError::Missing("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cookies_parsing() {
+        let mut request = HttpRequest::default();
+        assert!(request.cookies().is_empty());
+
+        request
+            .headers
+            .append("Cookie", "session=abc=def; theme = dark;; empty=; flag");
+        request.headers.append("Cookie", "lang=en");
+        request
+            .headers
+            .append("Cookie", "note=50%25%20off; raw=100%");
+
+        let cookies = request.cookies();
+        assert_eq!(cookies.get("session").unwrap(), "abc=def");
+        assert_eq!(cookies.get("theme").unwrap(), "dark");
+        assert_eq!(cookies.get("empty").unwrap(), "");
+        assert_eq!(cookies.get("lang").unwrap(), "en");
+        assert_eq!(cookies.get("note").unwrap(), "50% off");
+        assert_eq!(cookies.get("raw").unwrap(), "100%");
+        assert!(!cookies.contains_key("flag"));
+        assert_eq!(cookies.len(), 6);
+    }
+
     #[test]
     fn test_response_status_line_only() {
-        let mut response = HttpResponse::new("HTTP/1.1", 204, "No Content");
+        let mut response = HttpResponse::new(HttpVersion::Http11, 204, "No Content");
         let bytes = response.get_bytes();
         let response_str = String::from_utf8_lossy(&bytes);
 
@@ -415,7 +2316,7 @@ mod tests {
 
     #[test]
     fn test_response_with_headers_and_body() {
-        let mut response = HttpResponse::new("HTTP/1.1", 200, "OK");
+        let mut response = HttpResponse::new(HttpVersion::Http11, 200, "OK");
         response.insert_header("Content-Type", "text/html");
         response.set_body(b"<html><body>Hello</body></html>");
 
@@ -427,9 +2328,156 @@ mod tests {
         assert!(response_str.contains("\r\n\r\n<html><body>Hello</body></html>"));
     }
 
+    #[test]
+    fn test_response_has_date_header() {
+        let bytes = HttpResponse::ok().get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+
+        let date = response_str
+            .lines()
+            .find_map(|line| line.strip_prefix("Date: "))
+            .expect("Date header should be present");
+
+        let parts = date.split(' ').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 6);
+        assert!(parts[0].ends_with(','));
+        assert_eq!(parts[3].len(), 4);
+        assert_eq!(parts[4].split(':').count(), 3);
+        assert_eq!(parts[5], "GMT");
+
+        let mut response = HttpResponse::ok();
+        response.insert_header("Date", "Sun, 06 Nov 1994 08:49:37 GMT");
+        let bytes = response.get_bytes();
+        assert_eq!(String::from_utf8_lossy(&bytes).matches("Date: ").count(), 1);
+    }
+
+    #[test]
+    fn test_redirect_helpers() {
+        let cases = [
+            (
+                HttpResponse::redirect("/new", true),
+                "301 Moved Permanently",
+            ),
+            (HttpResponse::redirect("/new", false), "302 Found"),
+            (HttpResponse::redirect_see_other("/new"), "303 See Other"),
+            (
+                HttpResponse::redirect_temporary("/new"),
+                "307 Temporary Redirect",
+            ),
+        ];
+
+        for (mut response, status) in cases {
+            assert!(response.body.is_empty());
+
+            let bytes = response.get_bytes();
+            let response_str = String::from_utf8_lossy(&bytes);
+            assert!(response_str.starts_with(&format!("HTTP/1.1 {}\r\n", status)));
+            assert!(response_str.contains("Location: /new\r\n"));
+            assert!(response_str.contains("Content-Length: 0\r\n"));
+        }
+    }
+
+    #[test]
+    fn test_response_header_accessors() {
+        let mut response = HttpResponse::ok();
+        assert!(!response.has_header("X-Test"));
+        assert_eq!(response.get_header("X-Test"), None);
+
+        response.insert_header("X-Test", "one");
+        assert!(response.has_header("x-test"));
+        assert_eq!(response.get_header("X-TEST"), Some("one"));
+
+        response.insert_header("x-test", "two");
+        assert_eq!(response.get_header("X-Test"), Some("two"));
+
+        response.remove_header("X-TEST");
+        assert!(!response.has_header("X-Test"));
+
+        let bytes = response.get_bytes();
+        assert!(
+            !String::from_utf8_lossy(&bytes)
+                .to_lowercase()
+                .contains("x-test")
+        );
+    }
+
+    #[test]
+    fn test_response_emits_single_content_length() {
+        let mut response = HttpResponse::text("hello");
+        response.insert_header("content-length", "999");
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes).to_lowercase();
+        assert_eq!(response_str.matches("content-length").count(), 1);
+        assert!(response_str.contains("content-length: 5\r\n"));
+
+        let mut response = HttpResponse::ok();
+        response.insert_header("content-length", "42");
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes).to_lowercase();
+        assert_eq!(response_str.matches("content-length").count(), 1);
+        assert!(response_str.contains("content-length: 0\r\n"));
+
+        let mut response = HttpResponse::ok();
+        response.insert_header("content-length", "42");
+        response.strip_body();
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes).to_lowercase();
+        assert_eq!(response_str.matches("content-length").count(), 1);
+        assert!(response_str.contains("content-length: 42\r\n"));
+    }
+
+    #[test]
+    fn test_bodiless_statuses_have_no_length_or_body() {
+        for status_code in [100, 204, 304] {
+            let mut response = HttpResponse::from_status(status_code);
+            response.insert_header("ETag", "\"abc\"");
+            response.set_body(b"ignored");
+
+            let bytes = response.get_bytes();
+            let response_str = String::from_utf8_lossy(&bytes);
+            assert!(!response_str.contains("Content-Length"));
+            assert!(response_str.ends_with("ETag: \"abc\"\r\n\r\n"));
+        }
+    }
+
+    #[test]
+    fn test_response_emits_one_set_cookie_per_cookie() {
+        let mut response = HttpResponse::ok();
+        response.add_cookie("a", "1", &CookieOptions::default());
+        response.add_cookie(
+            "b",
+            "2",
+            &CookieOptions {
+                http_only: true,
+                ..Default::default()
+            },
+        );
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+
+        assert!(response_str.contains("Set-Cookie: a=1\r\n"));
+        assert!(response_str.contains("Set-Cookie: b=2; HttpOnly\r\n"));
+    }
+
+    #[test]
+    fn test_try_add_cookie_rejects_invalid_cookie() {
+        let mut response = HttpResponse::ok();
+        let result = response.try_add_cookie("a\r\nX-Injected", "1", &CookieOptions::default());
+
+        assert_eq!(
+            result,
+            Err(CookieError::InvalidName("a\r\nX-Injected".to_owned()))
+        );
+        assert!(!response.get_bytes().windows(10).any(|w| w == b"X-Injected"));
+    }
+
     #[test]
     fn test_content_length_calculation() {
-        let mut response = HttpResponse::new("HTTP/1.1", 200, "OK");
+        let mut response = HttpResponse::new(HttpVersion::Http11, 200, "OK");
         let body_data = b"Rust Programming";
         response.set_body(body_data);
 
@@ -439,4 +2487,185 @@ mod tests {
         let expected_header = format!("Content-Length: {}", body_data.len());
         assert!(response_str.contains(&expected_header));
     }
+
+    #[tokio::test]
+    async fn test_write_to_frames_with_content_length() {
+        let mut response = HttpResponse::text("hello");
+        let mut written = Vec::new();
+        let size = response.write_to(&mut written).await.unwrap();
+        let written = String::from_utf8(written).unwrap();
+
+        assert_eq!(size, 5);
+        assert!(written.contains("Content-Length: 5\r\n"));
+        assert!(!written.contains("Transfer-Encoding"));
+        assert!(written.ends_with("\r\n\r\nhello"));
+
+        let mut response = HttpResponse::from_reader(&b"streamed"[..], 8);
+        let mut written = Vec::new();
+        let size = response.write_to(&mut written).await.unwrap();
+        let written = String::from_utf8(written).unwrap();
+
+        assert_eq!(size, 8);
+        assert!(written.contains("Content-Length: 8\r\n"));
+        assert!(written.ends_with("\r\n\r\nstreamed"));
+    }
+
+    #[tokio::test]
+    async fn test_write_to_frames_chunked_streams() {
+        let mut response = HttpResponse::chunked(&b"streamed body"[..]);
+        response.insert_header("Connection", "close");
+        let mut written = Vec::new();
+        let size = response.write_to(&mut written).await.unwrap();
+        let written = String::from_utf8(written).unwrap();
+
+        assert_eq!(size, 13);
+        assert!(written.contains("Transfer-Encoding: chunked\r\n"));
+        assert!(written.contains("Connection: close\r\n"));
+        assert!(!written.contains("Content-Length"));
+        assert!(written.ends_with("\r\n\r\nd\r\nstreamed body\r\n0\r\n\r\n"));
+
+        let mut response = HttpResponse::from_reader(&b"short"[..], 10);
+        let err = response.write_to(&mut Vec::new()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_set_status_rewrites_status_line() {
+        let mut response = HttpResponse::text("cached");
+        assert_eq!(response.status_code(), 200);
+        assert_eq!(response.status_text(), "OK");
+
+        response.set_status(304, "Not Modified");
+        assert_eq!(response.status_code(), 304);
+        assert_eq!(response.status_text(), "Not Modified");
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 304 Not Modified\r\n"));
+        assert!(!response_str.contains("Content-Length"));
+        assert!(response_str.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_status_codes_are_validated() {
+        let response = HttpResponse::builder().status(404, "").build();
+        assert_eq!(response.status_code(), 404);
+        assert_eq!(response.status_text(), "Not Found");
+
+        let response = HttpResponse::builder().status(418, "I'm a teapot").build();
+        assert_eq!(response.status_text(), "I'm a teapot");
+
+        let mut response = HttpResponse::builder().status(999, "Nonsense").build();
+        assert_eq!(response.status_code(), 500);
+        assert_eq!(response.status_text(), "Internal Server Error");
+
+        response.set_status(0, "");
+        assert_eq!(response.status_code(), 500);
+
+        assert_eq!(
+            response.try_set_status(999, "Nonsense"),
+            Err(InvalidStatus(999))
+        );
+        assert_eq!(response.status_code(), 500);
+        assert_eq!(response.try_set_status(201, ""), Ok(()));
+        assert_eq!(response.status_text(), "Created");
+
+        response.set_status(200, "OK\r\nX-Injected: 1");
+        assert_eq!(response.status_text(), "OK");
+        let bytes = response.get_bytes();
+        assert!(!String::from_utf8_lossy(&bytes).contains("X-Injected"));
+    }
+
+    #[test]
+    fn test_named_status_constructors() {
+        let mut response = HttpResponse::ok();
+        let bytes = response.get_bytes();
+        assert!(String::from_utf8_lossy(&bytes).starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.body.is_empty());
+
+        let mut response = HttpResponse::internal_server_error();
+        let bytes = response.get_bytes();
+        assert!(
+            String::from_utf8_lossy(&bytes).starts_with("HTTP/1.1 500 Internal Server Error\r\n")
+        );
+
+        let mut response = HttpResponse::from_status(404);
+        let bytes = response.get_bytes();
+        assert!(String::from_utf8_lossy(&bytes).starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_body_helper_sets_optional_content_type() {
+        let mut response = HttpResponse::body(b"png".to_vec(), Some("image/png"));
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+
+        assert!(response_str.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response_str.contains("Content-Type: image/png\r\n"));
+        assert!(response_str.ends_with("\r\n\r\npng"));
+
+        let mut response = HttpResponse::body(b"raw".to_vec(), None);
+        let bytes = response.get_bytes();
+        assert!(!String::from_utf8_lossy(&bytes).contains("Content-Type"));
+    }
+
+    #[tokio::test]
+    async fn test_from_file_sets_type_and_length() {
+        let path =
+            std::env::temp_dir().join(format!("http-rs-{}-from-file.css", std::process::id()));
+        std::fs::write(&path, "body { color: red; }").unwrap();
+
+        let mut response = HttpResponse::from_file(&path).await.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response.get_header("Content-Type"),
+            Some("text/css; charset=UTF-8")
+        );
+        assert_eq!(response.body, b"body { color: red; }");
+
+        let bytes = response.get_bytes();
+        assert!(String::from_utf8_lossy(&bytes).contains("Content-Length: 20\r\n"));
+
+        std::fs::remove_file(&path).ok();
+        let error = HttpResponse::from_file(&path).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_internal_err_carries_message() {
+        let mut response = HttpResponse::internal_err("user data not set");
+        assert_eq!(response.body, b"user data not set");
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+    }
+
+    #[test]
+    fn test_builder_chains_status_headers_and_body() {
+        let mut response = HttpResponse::builder()
+            .version(HttpVersion::Http10)
+            .status(201, "Created")
+            .header("X-Foo", "bar")
+            .body(b"made".to_vec())
+            .build();
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+
+        assert!(response_str.starts_with("HTTP/1.0 201 Created\r\n"));
+        assert!(response_str.contains("X-Foo: bar\r\n"));
+        assert!(response_str.ends_with("\r\n\r\nmade"));
+    }
+
+    #[test]
+    fn test_builder_defaults_to_http11_200() {
+        let mut response = HttpResponse::builder().body("hi").build();
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+
+        assert!(response_str.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response_str.ends_with("\r\n\r\nhi"));
+    }
 }
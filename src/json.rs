@@ -0,0 +1,150 @@
+use std::fmt;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::http::{HttpRequest, HttpResponse};
+
+#[derive(Debug)]
+pub enum JsonError {
+    WrongContentType,
+    InvalidUtf8,
+    Deserialize(serde_json::Error),
+    Serialize(serde_json::Error),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::WrongContentType => write!(f, "content type is not application/json"),
+            JsonError::InvalidUtf8 => write!(f, "json body is not valid utf-8"),
+            JsonError::Deserialize(e) => write!(f, "failed to deserialize json: {}", e),
+            JsonError::Serialize(e) => write!(f, "failed to serialize json: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl HttpRequest {
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, JsonError> {
+        let is_json = self
+            .content_type()
+            .is_some_and(|content_type| content_type.is("application/json"));
+
+        if !is_json {
+            return Err(JsonError::WrongContentType);
+        }
+
+        let body = std::str::from_utf8(&self.body).map_err(|_| JsonError::InvalidUtf8)?;
+        return serde_json::from_str(body).map_err(JsonError::Deserialize);
+    }
+}
+
+impl HttpResponse {
+    pub fn from_json<T: Serialize + ?Sized>(value: &T) -> Result<HttpResponse, JsonError> {
+        let body = serde_json::to_vec(value).map_err(JsonError::Serialize)?;
+        return Ok(HttpResponse::body(
+            body,
+            Some("application/json; charset=UTF-8"),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct NewUser {
+        name: String,
+        age: u32,
+    }
+
+    fn json_request(content_type: Option<&str>, body: &[u8]) -> HttpRequest {
+        let mut req = HttpRequest {
+            body: body.to_vec(),
+            ..Default::default()
+        };
+
+        if let Some(content_type) = content_type {
+            req.headers.insert("Content-Type", content_type);
+        }
+
+        return req;
+    }
+
+    #[test]
+    fn test_json_deserializes_body() {
+        let req = json_request(
+            Some("application/json; charset=utf-8"),
+            br#"{"name": "alan", "age": 30}"#,
+        );
+
+        let user: NewUser = req.json().unwrap();
+        assert_eq!(
+            user,
+            NewUser {
+                name: "alan".to_string(),
+                age: 30
+            }
+        );
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let req = json_request(Some("text/plain"), br#"{"name": "alan", "age": 30}"#);
+        assert!(matches!(
+            req.json::<NewUser>(),
+            Err(JsonError::WrongContentType)
+        ));
+
+        let req = json_request(None, br#"{"name": "alan", "age": 30}"#);
+        assert!(matches!(
+            req.json::<NewUser>(),
+            Err(JsonError::WrongContentType)
+        ));
+
+        let req = json_request(Some("application/json"), b"{\"name\": \"\xff\"}");
+        assert!(matches!(req.json::<NewUser>(), Err(JsonError::InvalidUtf8)));
+
+        let req = json_request(Some("application/json"), br#"{"name": "alan"}"#);
+        assert!(matches!(
+            req.json::<NewUser>(),
+            Err(JsonError::Deserialize(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_json_round_trip() {
+        let user = NewUser {
+            name: "alan".to_string(),
+            age: 30,
+        };
+
+        let mut response = HttpResponse::from_json(&user).unwrap();
+        let content_type = response.get_header("Content-Type").unwrap().to_owned();
+        let req = json_request(Some(&content_type), &response.body);
+        assert_eq!(req.json::<NewUser>().unwrap(), user);
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response_str.contains("Content-Length: 24\r\n"));
+        assert!(response_str.ends_with(r#"{"name":"alan","age":30}"#));
+    }
+
+    #[test]
+    fn test_from_json_reports_serialize_error() {
+        let mut value = HashMap::new();
+        value.insert((1, 2), "tuple keys are not valid json keys");
+
+        assert!(matches!(
+            HttpResponse::from_json(&value),
+            Err(JsonError::Serialize(_))
+        ));
+    }
+}
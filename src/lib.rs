@@ -1,6 +1,38 @@
+#![allow(clippy::needless_return)]
+
+pub mod access_log;
+pub mod auth;
+pub mod compression;
+pub mod cookie;
+pub mod cors;
+pub mod date;
+pub mod headers;
 pub mod http;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod mime;
+pub mod proxy;
+pub mod rate_limit;
 pub mod router;
 pub mod server;
+pub mod static_files;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub use access_log::*;
+pub use auth::*;
+pub use compression::*;
+pub use cookie::*;
+pub use cors::*;
+pub use date::*;
+pub use headers::*;
 pub use http::*;
+#[cfg(feature = "json")]
+pub use json::*;
+pub use mime::*;
+pub use proxy::*;
+pub use rate_limit::*;
 pub use router::*;
 pub use server::*;
+pub use static_files::*;
+#[cfg(any(test, feature = "test-util"))]
+pub use testing::*;
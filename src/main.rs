@@ -1,67 +1,13 @@
-mod http;
-mod router;
-mod server;
+#![allow(clippy::needless_return)]
 
 use std::env;
-use std::path::Path;
-use std::pin::Pin;
 
-use tokio::fs;
-
-use crate::http::*;
-
-use crate::router::*;
-use crate::server::*;
-
-async fn get_file_bytes(path: &str) -> tokio::io::Result<Vec<u8>> {
-    let contents = fs::read(path).await?;
-    Ok(contents)
-}
-
-fn is_safe_path(user_path: &str) -> bool {
-    let path = Path::new(user_path);
-
-    for component in path.components() {
-        match component {
-            std::path::Component::Normal(_) => continue,
-            std::path::Component::CurDir => continue,
-            _ => return false,
-        }
-    }
-    true
-}
-
-fn global_route(
-    request: HttpRequest
-) -> Pin<Box<dyn Future<Output = HttpResponse> + Send>> {
-    return Box::pin(async move {
-        let stripped_path = {
-            if let Some(p) = request.path.strip_prefix("/") {
-                p
-            } else {
-                request.path.as_str()
-            }
-        };
-
-        if !is_safe_path(stripped_path) {
-            return HttpResponse::forbidden("cannot access that path");
-        }
-
-        let contents = get_file_bytes(stripped_path).await;
-
-        if let Err(_) = contents {
-            return HttpResponse::not_found("file not found");
-        }
-
-        let contents = contents.unwrap();
-        return HttpResponse::body(contents, None);
-    });
-}
+use http_rs::*;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<_> = env::args().skip(1).collect();
-    if args.len() != 2 && args.len() != 0 {
+    if args.len() != 2 && !args.is_empty() {
         println!("Usage: ./http ip port or ./http");
     }
 
@@ -73,10 +19,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let server = Server::new(port, ip);
+    let mut server = Server::new(port, ip);
+    server.on_request(common_log);
 
     let mut router: Router = Router::new(None);
-    router.get("*", Box::new(global_route));
+    let mut files = ServeDir::new(".");
+    files.set_list_directories(true);
+    router.get("*", files.handler());
 
     server.run(router).await?;
     return Ok(());
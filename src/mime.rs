@@ -0,0 +1,160 @@
+use std::{collections::HashMap, path::Path};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentType {
+    pub mime: String,
+    pub params: HashMap<String, String>,
+}
+
+impl ContentType {
+    pub fn parse(value: &str) -> Option<ContentType> {
+        let (mime, mut rest) = value.split_once(';').unwrap_or((value, ""));
+        let mime = mime.trim().to_ascii_lowercase();
+        let (kind, subtype) = mime.split_once('/')?;
+        if kind.is_empty() || subtype.is_empty() {
+            return None;
+        }
+
+        let mut params = HashMap::new();
+        while !rest.is_empty() {
+            let Some(equals) = rest.find('=').filter(|&i| !rest[..i].contains(';')) else {
+                rest = rest.split_once(';').map_or("", |(_, next)| next);
+                continue;
+            };
+
+            let key = rest[..equals].trim().to_ascii_lowercase();
+            let after_key = rest[equals + 1..].trim_start();
+            let value = match after_key.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted.find('"').unwrap_or(quoted.len());
+                    rest = &quoted[end..];
+                    &quoted[..end]
+                }
+                None => {
+                    let end = after_key.find(';').unwrap_or(after_key.len());
+                    rest = &after_key[end..];
+                    after_key[..end].trim()
+                }
+            };
+            rest = rest.split_once(';').map_or("", |(_, next)| next);
+
+            if !key.is_empty() {
+                params.insert(key, value.to_owned());
+            }
+        }
+
+        return Some(ContentType { mime, params });
+    }
+
+    pub fn is(&self, mime: &str) -> bool {
+        return self.mime.eq_ignore_ascii_case(mime);
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        return self
+            .params
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str);
+    }
+}
+
+pub fn content_type_for_extension(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("html") | Some("htm") => "text/html; charset=UTF-8",
+        Some("css") => "text/css; charset=UTF-8",
+        Some("js") | Some("mjs") => "text/javascript; charset=UTF-8",
+        Some("json") => "application/json",
+        Some("txt") => "text/plain; charset=UTF-8",
+        Some("xml") => "application/xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("ico") => "image/x-icon",
+        Some("webp") => "image/webp",
+        Some("wasm") => "application/wasm",
+        Some("pdf") => "application/pdf",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("mp4") => "video/mp4",
+        Some("mp3") => "audio/mpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_extensions() {
+        assert_eq!(
+            content_type_for_extension("index.html"),
+            "text/html; charset=UTF-8"
+        );
+        assert_eq!(
+            content_type_for_extension("static/css/site.CSS"),
+            "text/css; charset=UTF-8"
+        );
+        assert_eq!(
+            content_type_for_extension("app.js"),
+            "text/javascript; charset=UTF-8"
+        );
+        assert_eq!(content_type_for_extension("data.json"), "application/json");
+        assert_eq!(content_type_for_extension("logo.png"), "image/png");
+        assert_eq!(content_type_for_extension("photo.jpeg"), "image/jpeg");
+        assert_eq!(content_type_for_extension("icon.svg"), "image/svg+xml");
+        assert_eq!(
+            content_type_for_extension("module.wasm"),
+            "application/wasm"
+        );
+    }
+
+    #[test]
+    fn test_parse_content_type_with_charset() {
+        let content_type = ContentType::parse("Application/JSON; Charset=utf-8").unwrap();
+        assert_eq!(content_type.mime, "application/json");
+        assert!(content_type.is("application/json"));
+        assert_eq!(content_type.param("charset"), Some("utf-8"));
+
+        let content_type = ContentType::parse("text/plain; format; charset=\"us-ascii\"").unwrap();
+        assert_eq!(content_type.param("charset"), Some("us-ascii"));
+        assert_eq!(content_type.params.len(), 1);
+
+        assert!(ContentType::parse("json").is_none());
+        assert!(ContentType::parse("").is_none());
+    }
+
+    #[test]
+    fn test_parse_content_type_with_multipart_boundary() {
+        let content_type =
+            ContentType::parse("multipart/form-data; boundary=\"----abc;123\"").unwrap();
+        assert!(content_type.is("multipart/form-data"));
+        assert_eq!(content_type.param("boundary"), Some("----abc;123"));
+
+        let content_type =
+            ContentType::parse("multipart/form-data; boundary=----WebKitFormBoundary7MA4YWxk")
+                .unwrap();
+        assert_eq!(
+            content_type.param("Boundary"),
+            Some("----WebKitFormBoundary7MA4YWxk")
+        );
+    }
+
+    #[test]
+    fn test_unknown_extensions_default_to_octet_stream() {
+        assert_eq!(
+            content_type_for_extension("archive.unknown"),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            content_type_for_extension("Makefile"),
+            "application/octet-stream"
+        );
+    }
+}
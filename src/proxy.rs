@@ -0,0 +1,446 @@
+use std::{io, sync::Arc, time::Duration};
+
+use tokio::{
+    io::{AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+use crate::{
+    http::{HttpMethod, HttpRequest, HttpResponse, ParseOptions, read_response, write_stream},
+    router::HandlerWithoutUserData,
+};
+
+const HOP_BY_HOP: [&str; 9] = [
+    "Connection",
+    "Keep-Alive",
+    "Proxy-Connection",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "TE",
+    "Trailer",
+    "Transfer-Encoding",
+    "Upgrade",
+];
+
+#[derive(Debug, Clone)]
+pub struct Proxy {
+    upstream: Upstream,
+    timeout: Duration,
+    max_body_size: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Upstream {
+    authority: String,
+    host: String,
+    port: u16,
+    base_path: String,
+}
+
+impl Upstream {
+    fn parse(url: &str) -> Option<Upstream> {
+        let rest = url.strip_prefix("http://")?;
+        let (authority, base_path) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => (rest, ""),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port.parse::<u16>().ok()?),
+            _ => (authority, 80),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            return None;
+        }
+
+        return Some(Upstream {
+            authority: authority.to_owned(),
+            host: host.to_owned(),
+            port,
+            base_path: base_path.trim_end_matches('/').to_owned(),
+        });
+    }
+}
+
+fn is_hop_by_hop(name: &str, connection: &[String]) -> bool {
+    return HOP_BY_HOP.iter().any(|hop| hop.eq_ignore_ascii_case(name))
+        || connection
+            .iter()
+            .any(|token| token.eq_ignore_ascii_case(name));
+}
+
+fn connection_tokens<'a>(values: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    return values
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|token| token.trim().to_owned())
+        .filter(|token| !token.is_empty())
+        .collect();
+}
+
+fn percent_encode(text: &str, keep: &[u8]) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ if keep.contains(&byte) => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    return encoded;
+}
+
+fn upstream_target(upstream: &Upstream, request: &HttpRequest) -> String {
+    let path = match request.params.get("*") {
+        Some(rest) => format!("/{}", rest),
+        None => request.path.clone(),
+    };
+
+    let mut target = format!(
+        "{}{}",
+        upstream.base_path,
+        percent_encode(&path, b"/:@!$&'()*+,;=")
+    );
+    if target.is_empty() {
+        target.push('/');
+    }
+
+    if !request.query_pairs.is_empty() {
+        let query = request
+            .query_pairs
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) => format!(
+                    "{}={}",
+                    percent_encode(key, b""),
+                    percent_encode(value, b"")
+                ),
+                None => percent_encode(key, b""),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        target = format!("{}?{}", target, query);
+    }
+
+    return target;
+}
+
+fn upstream_request(upstream: &Upstream, request: &HttpRequest, streamed: bool) -> Vec<u8> {
+    let connection = connection_tokens(request.header_all("Connection"));
+
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\n",
+        request.method.as_str(),
+        upstream_target(upstream, request),
+        upstream.authority
+    );
+    for (key, value) in request.headers.iter() {
+        let skipped = ["Host", "Content-Length", "X-Forwarded-For"]
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(key));
+        if !skipped && !is_hop_by_hop(key, &connection) {
+            head.push_str(&format!("{}: {}\r\n", key, value));
+        }
+    }
+
+    let forwarded = request
+        .header_all("X-Forwarded-For")
+        .into_iter()
+        .map(str::to_owned)
+        .chain(request.peer_addr.map(|addr| addr.ip().to_string()))
+        .collect::<Vec<_>>();
+    if !forwarded.is_empty() {
+        head.push_str(&format!("X-Forwarded-For: {}\r\n", forwarded.join(", ")));
+    }
+
+    let framed =
+        request.header("Content-Length").is_some() || request.header("Transfer-Encoding").is_some();
+    if streamed {
+        head.push_str("Transfer-Encoding: chunked\r\n");
+    } else if framed || !request.body.is_empty() {
+        head.push_str(&format!("Content-Length: {}\r\n", request.body.len()));
+    }
+    head.push_str("Connection: close\r\n\r\n");
+
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(&request.body);
+    return bytes;
+}
+
+impl Proxy {
+    pub fn new(upstream_base_url: &str) -> Proxy {
+        let upstream = Upstream::parse(upstream_base_url).unwrap_or_else(|| {
+            panic!(
+                "invalid upstream url {:?}: expected http://host[:port][/path]",
+                upstream_base_url
+            )
+        });
+
+        Proxy {
+            upstream,
+            timeout: Duration::from_secs(30),
+            max_body_size: 16 * 1024 * 1024,
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        return self;
+    }
+
+    pub fn set_max_body_size(&mut self, max_body_size: usize) -> &mut Self {
+        self.max_body_size = max_body_size;
+        return self;
+    }
+
+    pub fn handler(&self) -> HandlerWithoutUserData {
+        let proxy = Arc::new(self.clone());
+        Box::new(move |req| {
+            let proxy = Arc::clone(&proxy);
+            Box::pin(async move { proxy.serve(req).await })
+        })
+    }
+
+    pub async fn serve(&self, request: HttpRequest) -> HttpResponse {
+        let authority = &self.upstream.authority;
+        return match tokio::time::timeout(self.timeout, self.forward(&request)).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                eprintln!("Proxy to {} failed: {}", authority, e);
+                HttpResponse::from_status(502)
+            }
+            Err(_) => {
+                eprintln!("Proxy to {} timed out", authority);
+                HttpResponse::from_status(504)
+            }
+        };
+    }
+
+    async fn forward(&self, request: &HttpRequest) -> io::Result<HttpResponse> {
+        let upstream = &self.upstream;
+        let body = request.body_reader();
+
+        let mut stream = TcpStream::connect((upstream.host.as_str(), upstream.port)).await?;
+        stream
+            .write_all(&upstream_request(upstream, request, body.is_some()))
+            .await?;
+        if let Some(body) = body {
+            write_stream(&mut stream, body, None).await?;
+        }
+
+        let mut reader = BufReader::new(stream);
+        let options = ParseOptions {
+            max_body_size: self.max_body_size,
+            ..Default::default()
+        };
+        let mut response =
+            read_response(&mut reader, request.method == HttpMethod::Head, &options).await?;
+
+        let connection = connection_tokens(response.get_header("Connection"));
+        for name in HOP_BY_HOP
+            .iter()
+            .map(|name| name.to_string())
+            .chain(connection)
+        {
+            response.remove_header(&name);
+        }
+
+        return Ok(response);
+    }
+}
+
+pub fn proxy_to(upstream_base_url: &str) -> HandlerWithoutUserData {
+    return Proxy::new(upstream_base_url).handler();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{http::BodyFraming, router::Router, testing::TestServer};
+
+    async fn upstream_server() -> TestServer {
+        let mut router: Router = Router::new(None);
+        for path in ["/v1/*", "/v1"] {
+            router.get(
+                path,
+                Box::new(|req| {
+                    Box::pin(async move {
+                        let mut res = HttpResponse::text(&format!(
+                            "{} {} {:?} {:?} {:?}",
+                            req.method.as_str(),
+                            req.path,
+                            req.query_pairs,
+                            req.header("X-Token"),
+                            req.header("Keep-Alive"),
+                        ));
+                        res.insert_header("X-Upstream", "1");
+                        res.insert_header("Keep-Alive", "timeout=5");
+                        res
+                    })
+                }),
+            );
+        }
+        router.post(
+            "/v1/*",
+            Box::new(|req| {
+                Box::pin(async move {
+                    let mut res = HttpResponse::chunked(std::io::Cursor::new(req.body));
+                    res.set_status(201, "Created");
+                    res
+                })
+            }),
+        );
+
+        return TestServer::spawn(router).await.unwrap();
+    }
+
+    fn proxied_request(method: HttpMethod, path: &str) -> HttpRequest {
+        let mut req = HttpRequest {
+            method,
+            path: path.to_string(),
+            ..Default::default()
+        };
+        req.headers.insert("X-Token", "abc");
+        req.headers.insert("Keep-Alive", "timeout=10");
+        req.headers.insert("Host", "front.example");
+        return req;
+    }
+
+    #[tokio::test]
+    async fn test_proxy_forwards_requests_upstream() {
+        let upstream = upstream_server().await;
+        let mut router: Router = Router::new(None);
+        let upstream_url = format!("http://{}/v1", upstream.addr());
+        router.get("/api/*", proxy_to(&upstream_url));
+        router.post("/api/*", proxy_to(&upstream_url));
+
+        let mut req = proxied_request(HttpMethod::Get, "/api/users/a b");
+        req.query_pairs = vec![
+            ("q".to_owned(), Some("x&y".to_owned())),
+            ("flag".to_owned(), None),
+        ];
+        let res = router.fetch(req).await.unwrap();
+        assert_eq!(res.status_code, 200);
+        assert_eq!(
+            res.body,
+            br#"GET /v1/users/a b [("q", Some("x&y")), ("flag", None)] Some("abc") None"#
+        );
+        assert_eq!(res.get_header("X-Upstream"), Some("1"));
+        assert!(!res.has_header("Keep-Alive"));
+        assert!(!res.has_header("Connection"));
+
+        let mut req = proxied_request(HttpMethod::Post, "/api/upload");
+        req.headers.insert("Transfer-Encoding", "chunked");
+        req.body = b"payload".to_vec();
+        let res = router.fetch(req).await.unwrap();
+        assert_eq!(res.status_code(), 201);
+        assert_eq!(res.status_text(), "Created");
+        assert_eq!(res.body, b"payload");
+        assert!(!res.has_header("Transfer-Encoding"));
+
+        let res = router
+            .fetch(proxied_request(HttpMethod::Head, "/api/users/7"))
+            .await
+            .unwrap();
+        assert_eq!(res.status_code, 200);
+        assert!(res.body.is_empty());
+        assert_eq!(res.get_header("X-Upstream"), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_reports_unreachable_upstream_as_502() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let mut router: Router = Router::new(None);
+        router.get("/*", proxy_to(&format!("http://{}", addr)));
+
+        let res = router
+            .fetch(proxied_request(HttpMethod::Get, "/anything"))
+            .await
+            .unwrap();
+        assert_eq!(res.status_code, 502);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_forwards_streamed_request_bodies() {
+        let upstream = upstream_server().await;
+        let proxy = Proxy::new(&format!("http://{}/v1", upstream.addr()));
+
+        let mut req = proxied_request(HttpMethod::Post, "/upload");
+        req.headers.insert("Content-Length", "11");
+        let sender = req.attach_body_stream(BodyFraming::Length(11));
+        tokio::spawn(async move {
+            for chunk in ["hello", " ", "world"] {
+                sender.send(Ok(chunk.as_bytes().to_vec())).await.unwrap();
+            }
+        });
+
+        let res = proxy.serve(req).await;
+        assert_eq!(res.status_code(), 201);
+        assert_eq!(res.body, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_proxy_times_out_and_caps_upstream_bodies() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stalled = tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let mut proxy = Proxy::new(&format!("http://{}", addr));
+        proxy.set_timeout(Duration::from_millis(50));
+        let res = proxy.serve(proxied_request(HttpMethod::Get, "/slow")).await;
+        assert_eq!(res.status_code(), 504);
+        stalled.abort();
+
+        let upstream = upstream_server().await;
+        let mut proxy = Proxy::new(&format!("http://{}/v1", upstream.addr()));
+        proxy.set_max_body_size(8);
+        let res = proxy
+            .serve(proxied_request(HttpMethod::Get, "/users/7"))
+            .await;
+        assert_eq!(res.status_code(), 502);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_rejects_endless_upstream_headers() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nX-Long: ").await.ok();
+            let filler = vec![b'a'; 64 * 1024];
+            for _ in 0..64 {
+                if stream.write_all(&filler).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let proxy = Proxy::new(&format!("http://{}", addr));
+        let res = proxy.serve(proxied_request(HttpMethod::Get, "/")).await;
+        assert_eq!(res.status_code(), 502);
+    }
+
+    #[test]
+    fn test_upstream_url_parsing() {
+        let upstream = Upstream::parse("http://[::1]:8080/base/").unwrap();
+        assert_eq!(upstream.host, "::1");
+        assert_eq!(upstream.port, 8080);
+        assert_eq!(upstream.base_path, "/base");
+
+        let upstream = Upstream::parse("http://example.com").unwrap();
+        assert_eq!((upstream.host.as_str(), upstream.port), ("example.com", 80));
+        assert_eq!(upstream.base_path, "");
+
+        assert!(Upstream::parse("https://example.com").is_none());
+        assert!(Upstream::parse("http://example.com:port").is_none());
+    }
+}
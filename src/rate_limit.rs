@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{http::HttpResponse, router::Middleware};
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+struct RateLimitState {
+    buckets: HashMap<IpAddr, Bucket>,
+    last_prune: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: u32,
+    window: Duration,
+    state: Arc<Mutex<RateLimitState>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests: u32, window: Duration) -> RateLimiter {
+        RateLimiter {
+            capacity: requests.max(1),
+            window,
+            state: Arc::new(Mutex::new(RateLimitState {
+                buckets: HashMap::new(),
+                last_prune: Instant::now(),
+            })),
+        }
+    }
+
+    pub fn middleware(&self) -> Middleware {
+        let limiter = self.clone();
+        Box::new(move |req, next| {
+            let limiter = limiter.clone();
+            Box::pin(async move {
+                let Some(ip) = req.peer_addr.map(|addr| addr.ip()) else {
+                    return next.run(req).await;
+                };
+
+                match limiter.acquire(ip) {
+                    Ok(()) => next.run(req).await,
+                    Err(retry_after) => {
+                        let mut res = HttpResponse::from_status(429);
+                        let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+                        res.insert_header("Retry-After", &seconds.to_string());
+                        res
+                    }
+                }
+            })
+        })
+    }
+
+    fn acquire(&self, ip: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        // A bucket left alone for a whole window has refilled completely, so
+        // forgetting it is indistinguishable from keeping it.
+        if now.duration_since(state.last_prune) >= self.window {
+            let window = self.window;
+            state
+                .buckets
+                .retain(|_, bucket| now.duration_since(bucket.updated) < window);
+            state.last_prune = now;
+        }
+
+        let capacity = self.capacity as f64;
+        let rate = capacity / self.window.as_secs_f64();
+        let bucket = state.buckets.entry(ip).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let refilled = now.duration_since(bucket.updated).as_secs_f64() * rate;
+        bucket.tokens = (bucket.tokens + refilled).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        return Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::{
+        http::{HttpMethod, HttpRequest},
+        router::Router,
+    };
+
+    fn limited_router(limiter: &RateLimiter) -> Router {
+        let mut router: Router = Router::new(None);
+        router.use_middleware(limiter.middleware());
+        router.get(
+            "/api",
+            Box::new(|_req| Box::pin(async move { HttpResponse::text("ok") })),
+        );
+        return router;
+    }
+
+    fn request_from(peer: &str) -> HttpRequest {
+        return HttpRequest {
+            method: HttpMethod::Get,
+            path: "/api".to_string(),
+            peer_addr: Some(peer.parse::<SocketAddr>().unwrap()),
+            ..Default::default()
+        };
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_limits_bursts_per_ip() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+        let router = limited_router(&limiter);
+
+        for _ in 0..3 {
+            let res = router.fetch(request_from("192.0.2.1:4000")).await.unwrap();
+            assert_eq!(res.status_code, 200);
+        }
+
+        let res = router.fetch(request_from("192.0.2.1:4001")).await.unwrap();
+        assert_eq!(res.status_code, 429);
+        assert_eq!(res.get_header("Retry-After"), Some("20"));
+
+        let res = router.fetch(request_from("192.0.2.2:4000")).await.unwrap();
+        assert_eq!(res.status_code, 200);
+
+        tokio::time::advance(Duration::from_secs(20)).await;
+        let res = router.fetch(request_from("192.0.2.1:4000")).await.unwrap();
+        assert_eq!(res.status_code, 200);
+
+        let res = router.fetch(request_from("192.0.2.1:4000")).await.unwrap();
+        assert_eq!(res.status_code, 429);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_prunes_idle_buckets() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(10));
+        let router = limited_router(&limiter);
+
+        router.fetch(request_from("192.0.2.1:4000")).await.unwrap();
+        router.fetch(request_from("192.0.2.2:4000")).await.unwrap();
+        assert_eq!(limiter.state.lock().unwrap().buckets.len(), 2);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        router.fetch(request_from("192.0.2.3:4000")).await.unwrap();
+        assert_eq!(limiter.state.lock().unwrap().buckets.len(), 1);
+    }
+}
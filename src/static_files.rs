@@ -0,0 +1,870 @@
+use std::{
+    fs::Metadata,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    date::{http_date, parse_http_date},
+    http::{HttpMethod, HttpRequest, HttpResponse},
+    mime::content_type_for_extension,
+    router::HandlerWithoutUserData,
+};
+
+#[derive(Debug, Clone)]
+pub struct ServeDir {
+    root: PathBuf,
+    index_file: Option<String>,
+    list_directories: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RangeError {
+    Malformed,
+    Unsatisfiable,
+}
+
+const MAX_RANGES: usize = 16;
+
+pub fn parse_byte_range(header: &str, length: u64) -> Result<(u64, u64), RangeError> {
+    let spec = header
+        .trim()
+        .strip_prefix("bytes=")
+        .ok_or(RangeError::Malformed)?;
+
+    if spec.contains(',') {
+        return Err(RangeError::Malformed);
+    }
+
+    return parse_range_spec(spec, length);
+}
+
+pub fn parse_byte_ranges(header: &str, length: u64) -> Result<Vec<(u64, u64)>, RangeError> {
+    let specs = header
+        .trim()
+        .strip_prefix("bytes=")
+        .ok_or(RangeError::Malformed)?
+        .split(',')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+        .collect::<Vec<_>>();
+
+    if specs.is_empty() {
+        return Err(RangeError::Malformed);
+    }
+    if specs.len() > MAX_RANGES {
+        return Err(RangeError::Unsatisfiable);
+    }
+
+    let mut ranges = Vec::new();
+    for spec in specs {
+        match parse_range_spec(spec, length) {
+            Ok(range) => ranges.push(range),
+            Err(RangeError::Unsatisfiable) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    if ranges.is_empty() {
+        return Err(RangeError::Unsatisfiable);
+    }
+
+    ranges.sort();
+    let mut coalesced: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match coalesced.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => coalesced.push((start, end)),
+        }
+    }
+
+    return Ok(coalesced);
+}
+
+fn parse_range_spec(spec: &str, length: u64) -> Result<(u64, u64), RangeError> {
+    let (start, end) = spec.trim().split_once('-').ok_or(RangeError::Malformed)?;
+    let parse = |value: &str| {
+        value
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| RangeError::Malformed)
+    };
+
+    let (start, end) = match (start.trim().is_empty(), end.trim().is_empty()) {
+        (true, true) => return Err(RangeError::Malformed),
+        (true, false) => {
+            let suffix = parse(end)?;
+            if suffix == 0 || length == 0 {
+                return Err(RangeError::Unsatisfiable);
+            }
+
+            (length.saturating_sub(suffix), length - 1)
+        }
+        (false, true) => (parse(start)?, length.saturating_sub(1)),
+        (false, false) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if end < start {
+                return Err(RangeError::Malformed);
+            }
+
+            (start, end.min(length.saturating_sub(1)))
+        }
+    };
+
+    if start >= length {
+        return Err(RangeError::Unsatisfiable);
+    }
+
+    return Ok((start, end));
+}
+
+pub fn etag_for(contents: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in contents {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    return format!("\"{:x}-{:x}\"", contents.len(), hash);
+}
+
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    return if_none_match.split(',').any(|candidate| {
+        let candidate = candidate.trim();
+        let candidate = candidate.strip_prefix("W/").unwrap_or(candidate);
+        return candidate == "*" || candidate == etag;
+    });
+}
+
+fn metadata_etag(metadata: &Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_nanos())
+        .unwrap_or_default();
+
+    return format!("\"{:x}-{:x}\"", metadata.len(), modified);
+}
+
+fn modified_since(if_modified_since: &str, modified: SystemTime) -> bool {
+    let Some(since) = parse_http_date(if_modified_since) else {
+        return true;
+    };
+
+    let seconds = |time: SystemTime| {
+        time.duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    };
+    return seconds(modified) > seconds(since);
+}
+
+fn insert_validators(response: &mut HttpResponse, etag: &str, modified: Option<SystemTime>) {
+    response.insert_header("ETag", etag);
+    if let Some(modified) = modified {
+        response.insert_header("Last-Modified", &http_date(modified));
+    }
+}
+
+fn not_modified(
+    request: &HttpRequest,
+    etag: &str,
+    modified: Option<SystemTime>,
+) -> Option<HttpResponse> {
+    let unchanged = match request.header("If-None-Match") {
+        Some(if_none_match) => etag_matches(if_none_match, etag),
+        None => match (request.header("If-Modified-Since"), modified) {
+            (Some(if_modified_since), Some(modified)) => {
+                !modified_since(if_modified_since, modified)
+            }
+            _ => false,
+        },
+    };
+
+    if !unchanged {
+        return None;
+    }
+
+    let mut response = HttpResponse::from_status(304);
+    insert_validators(&mut response, etag, modified);
+    return Some(response);
+}
+
+fn head_response(
+    request: &HttpRequest,
+    path: &str,
+    length: u64,
+    etag: &str,
+    modified: Option<SystemTime>,
+) -> HttpResponse {
+    if let Some(response) = not_modified(request, etag, modified) {
+        return response;
+    }
+
+    let mut response = HttpResponse::from_status(200);
+    response.insert_header("Content-Type", content_type_for_extension(path));
+    response.insert_header("Content-Length", &length.to_string());
+    response.insert_header("Accept-Ranges", "bytes");
+    insert_validators(&mut response, etag, modified);
+    return response;
+}
+
+pub fn file_response(request: &HttpRequest, path: &str, contents: Vec<u8>) -> HttpResponse {
+    let etag = etag_for(&contents);
+    return file_response_with_etag(request, path, contents, &etag, None);
+}
+
+fn file_response_with_etag(
+    request: &HttpRequest,
+    path: &str,
+    contents: Vec<u8>,
+    etag: &str,
+    modified: Option<SystemTime>,
+) -> HttpResponse {
+    let content_type = content_type_for_extension(path);
+    let length = contents.len() as u64;
+
+    if let Some(response) = not_modified(request, etag, modified) {
+        return response;
+    }
+
+    let ranges = request
+        .header("Range")
+        .map(|range| parse_byte_ranges(range, length));
+
+    let mut response = match ranges {
+        Some(Ok(ranges)) if ranges.len() > 1 => {
+            multipart_ranges_response(&contents, &ranges, content_type, etag)
+        }
+        Some(Ok(ranges)) => {
+            let (start, end) = ranges[0];
+            let mut response = HttpResponse::from_status(206);
+            response.insert_header("Content-Type", content_type);
+            response.insert_header(
+                "Content-Range",
+                &format!("bytes {}-{}/{}", start, end, length),
+            );
+            response.set_body(&contents[start as usize..=end as usize]);
+            response
+        }
+        Some(Err(RangeError::Unsatisfiable)) => {
+            let mut response = HttpResponse::from_status(416);
+            response.insert_header("Content-Range", &format!("bytes */{}", length));
+            response
+        }
+        Some(Err(RangeError::Malformed)) | None => HttpResponse::body(contents, Some(content_type)),
+    };
+
+    response.insert_header("Accept-Ranges", "bytes");
+    insert_validators(&mut response, etag, modified);
+    return response;
+}
+
+fn multipart_ranges_response(
+    contents: &[u8],
+    ranges: &[(u64, u64)],
+    content_type: &str,
+    etag: &str,
+) -> HttpResponse {
+    let boundary = format!("byteranges-{}", etag.trim_matches('"'));
+    let length = contents.len();
+
+    let mut body = Vec::new();
+    for &(start, end) in ranges {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Type: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+                boundary, content_type, start, end, length
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&contents[start as usize..=end as usize]);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+    let mut response = HttpResponse::from_status(206);
+    response.insert_header(
+        "Content-Type",
+        &format!("multipart/byteranges; boundary={}", boundary),
+    );
+    response.set_body(&body);
+    return response;
+}
+
+impl ServeDir {
+    pub fn new(root: impl Into<PathBuf>) -> ServeDir {
+        ServeDir {
+            root: root.into(),
+            index_file: Some("index.html".to_owned()),
+            list_directories: false,
+        }
+    }
+
+    pub fn set_index_file(&mut self, index_file: Option<&str>) -> &mut Self {
+        self.index_file = index_file.map(str::to_owned);
+        return self;
+    }
+
+    pub fn set_list_directories(&mut self, list_directories: bool) -> &mut Self {
+        self.list_directories = list_directories;
+        return self;
+    }
+
+    pub fn handler(&self) -> HandlerWithoutUserData {
+        let dir = Arc::new(self.clone());
+        Box::new(move |req| {
+            let dir = Arc::clone(&dir);
+            Box::pin(async move { dir.serve(req).await })
+        })
+    }
+
+    async fn confine(root: &Path, candidate: PathBuf) -> Result<PathBuf, HttpResponse> {
+        let path = tokio::fs::canonicalize(candidate)
+            .await
+            .map_err(|_| HttpResponse::not_found("file not found"))?;
+
+        if !path.starts_with(root) {
+            return Err(HttpResponse::forbidden("cannot access that path"));
+        }
+
+        return Ok(path);
+    }
+
+    async fn resolve(&self, request_path: &str) -> Result<PathBuf, HttpResponse> {
+        let relative = Path::new(request_path.trim_start_matches('/'));
+        let confined = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+        if !confined {
+            return Err(HttpResponse::forbidden("cannot access that path"));
+        }
+
+        let root = tokio::fs::canonicalize(&self.root)
+            .await
+            .map_err(|_| HttpResponse::not_found("file not found"))?;
+        let path = ServeDir::confine(&root, root.join(relative)).await?;
+
+        if !path.is_dir() {
+            return Ok(path);
+        }
+
+        let index = self
+            .index_file
+            .as_ref()
+            .map(|index_file| path.join(index_file))
+            .filter(|index| index.exists());
+
+        return match index {
+            Some(index) => ServeDir::confine(&root, index).await,
+            None if self.list_directories => Ok(path),
+            None => Err(HttpResponse::not_found("file not found")),
+        };
+    }
+
+    pub async fn serve(&self, request: HttpRequest) -> HttpResponse {
+        let request_path = match request.params.get("*") {
+            Some(rest) => rest.as_str(),
+            None => request.path.as_str(),
+        };
+
+        let path = match self.resolve(request_path).await {
+            Ok(path) => path,
+            Err(response) => return response,
+        };
+
+        if path.is_dir() {
+            return directory_listing(&request.path, &path).await;
+        }
+
+        let Ok(metadata) = tokio::fs::metadata(&path).await else {
+            return HttpResponse::not_found("file not found");
+        };
+        let etag = metadata_etag(&metadata);
+        let modified = metadata.modified().ok();
+        let path = path.to_string_lossy();
+
+        if request.method == HttpMethod::Head {
+            return head_response(&request, &path, metadata.len(), &etag, modified);
+        }
+
+        return match tokio::fs::read(path.as_ref()).await {
+            Ok(contents) => file_response_with_etag(&request, &path, contents, &etag, modified),
+            Err(_) => HttpResponse::not_found("file not found"),
+        };
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    return escaped;
+}
+
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    return encoded;
+}
+
+async fn directory_listing(request_path: &str, dir: &Path) -> HttpResponse {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return HttpResponse::not_found("file not found");
+    };
+
+    let mut names = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let is_dir = entry
+            .file_type()
+            .await
+            .is_ok_and(|file_type| file_type.is_dir());
+        names.push((entry.file_name().to_string_lossy().into_owned(), is_dir));
+    }
+    names.sort();
+
+    let base: String = request_path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", encode_path_segment(segment)))
+        .collect();
+    let title = escape_html(request_path);
+
+    let mut body = format!(
+        "<!DOCTYPE html>\n<html>\n<head><title>Index of {title}</title></head>\n<body>\n<h1>Index of {title}</h1>\n<ul>\n"
+    );
+    for (name, is_dir) in names {
+        let slash = if is_dir { "/" } else { "" };
+        body.push_str(&format!(
+            "<li><a href=\"{base}/{}{slash}\">{}{slash}</a></li>\n",
+            encode_path_segment(&name),
+            escape_html(&name)
+        ));
+    }
+    body.push_str("</ul>\n</body>\n</html>\n");
+
+    return HttpResponse::html(&body);
+}
+
+pub fn serve_dir(root: impl Into<PathBuf>) -> HandlerWithoutUserData {
+    return ServeDir::new(root).handler();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::Router;
+
+    fn header_request(name: &str, value: &str) -> HttpRequest {
+        let mut request = HttpRequest::default();
+        request.headers.insert(name, value);
+        return request;
+    }
+
+    #[test]
+    fn test_parse_byte_range_forms() {
+        assert_eq!(parse_byte_range("bytes=0-99", 1000), Ok((0, 99)));
+        assert_eq!(parse_byte_range("bytes=500-", 1000), Ok((500, 999)));
+        assert_eq!(parse_byte_range("bytes=-500", 1000), Ok((500, 999)));
+        assert_eq!(parse_byte_range("bytes=-5000", 1000), Ok((0, 999)));
+        assert_eq!(parse_byte_range("bytes=900-5000", 1000), Ok((900, 999)));
+    }
+
+    #[test]
+    fn test_parse_byte_range_errors() {
+        assert_eq!(
+            parse_byte_range("bytes=1000-", 1000),
+            Err(RangeError::Unsatisfiable)
+        );
+        assert_eq!(
+            parse_byte_range("bytes=-0", 1000),
+            Err(RangeError::Unsatisfiable)
+        );
+        assert_eq!(
+            parse_byte_range("items=0-1", 1000),
+            Err(RangeError::Malformed)
+        );
+        assert_eq!(
+            parse_byte_range("bytes=5-1", 1000),
+            Err(RangeError::Malformed)
+        );
+        assert_eq!(
+            parse_byte_range("bytes=-", 1000),
+            Err(RangeError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_file_response_partial_content() {
+        let request = header_request("Range", "bytes=2-5");
+        let mut response = file_response(&request, "file.txt", b"0123456789".to_vec());
+        assert_eq!(response.body, b"2345");
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 206 Partial Content\r\n"));
+        assert!(response_str.contains("Content-Range: bytes 2-5/10\r\n"));
+        assert!(response_str.contains("Content-Length: 4\r\n"));
+    }
+
+    #[test]
+    fn test_parse_byte_ranges_coalesces_and_limits() {
+        assert_eq!(
+            parse_byte_ranges("bytes=200-299, 0-99", 1000),
+            Ok(vec![(0, 99), (200, 299)])
+        );
+        assert_eq!(
+            parse_byte_ranges("bytes=0-99,50-149,150-160,-10", 1000),
+            Ok(vec![(0, 160), (990, 999)])
+        );
+        assert_eq!(parse_byte_ranges("bytes=0-9,2000-", 1000), Ok(vec![(0, 9)]));
+        assert_eq!(
+            parse_byte_ranges("bytes=2000-,3000-", 1000),
+            Err(RangeError::Unsatisfiable)
+        );
+        assert_eq!(
+            parse_byte_ranges("bytes=0-9,x-1", 1000),
+            Err(RangeError::Malformed)
+        );
+
+        let many = (0..17)
+            .map(|i| format!("{}-{}", i * 10, i * 10 + 1))
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(
+            parse_byte_ranges(&format!("bytes={}", many), 1000),
+            Err(RangeError::Unsatisfiable)
+        );
+    }
+
+    #[test]
+    fn test_file_response_multiple_ranges() {
+        let request = header_request("Range", "bytes=0-1,6-7");
+        let contents = b"0123456789".to_vec();
+        let etag = etag_for(&contents);
+        let mut response = file_response(&request, "file.txt", contents);
+
+        let boundary = format!("byteranges-{}", etag.trim_matches('"'));
+        let expected = format!(
+            "--{b}\r\nContent-Type: text/plain; charset=UTF-8\r\nContent-Range: bytes 0-1/10\r\n\r\n01\r\n\
+             --{b}\r\nContent-Type: text/plain; charset=UTF-8\r\nContent-Range: bytes 6-7/10\r\n\r\n67\r\n\
+             --{b}--\r\n",
+            b = boundary
+        );
+        assert_eq!(String::from_utf8_lossy(&response.body), expected);
+        assert!(!response.has_header("Content-Range"));
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 206 Partial Content\r\n"));
+        assert!(response_str.contains(&format!(
+            "Content-Type: multipart/byteranges; boundary={}\r\n",
+            boundary
+        )));
+
+        let request = header_request("Range", "bytes=0-3,2-5");
+        let response = file_response(&request, "file.txt", b"0123456789".to_vec());
+        assert_eq!(response.body, b"012345");
+        assert_eq!(response.get_header("Content-Range"), Some("bytes 0-5/10"));
+    }
+
+    #[test]
+    fn test_file_response_unsatisfiable_range() {
+        let request = header_request("Range", "bytes=20-");
+        let mut response = file_response(&request, "file.txt", b"0123456789".to_vec());
+        assert!(response.body.is_empty());
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 416 Range Not Satisfiable\r\n"));
+        assert!(response_str.contains("Content-Range: bytes */10\r\n"));
+    }
+
+    #[test]
+    fn test_file_response_without_range_is_full() {
+        let mut response =
+            file_response(&HttpRequest::default(), "file.txt", b"0123456789".to_vec());
+        assert_eq!(response.body, b"0123456789");
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response_str.contains("Accept-Ranges: bytes\r\n"));
+    }
+
+    #[test]
+    fn test_etag_depends_on_contents() {
+        assert_eq!(etag_for(b"hello"), etag_for(b"hello"));
+        assert_ne!(etag_for(b"hello"), etag_for(b"world"));
+        assert!(etag_for(b"hello").starts_with('"'));
+    }
+
+    #[test]
+    fn test_file_response_sends_etag() {
+        let mut response = file_response(&HttpRequest::default(), "file.txt", b"hello".to_vec());
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response_str.contains(&format!("ETag: {}\r\n", etag_for(b"hello"))));
+        assert!(response_str.ends_with("\r\n\r\nhello"));
+    }
+
+    #[test]
+    fn test_file_response_if_none_match() {
+        let etag = etag_for(b"hello");
+
+        let request = header_request("If-None-Match", &format!("\"other\", W/{}", etag));
+        let mut response = file_response(&request, "file.txt", b"hello".to_vec());
+        assert!(response.body.is_empty());
+
+        let bytes = response.get_bytes();
+        let response_str = String::from_utf8_lossy(&bytes);
+        assert!(response_str.starts_with("HTTP/1.1 304 Not Modified\r\n"));
+        assert!(response_str.contains(&format!("ETag: {}\r\n", etag)));
+
+        let request = header_request("If-None-Match", "\"stale\"");
+        let response = file_response(&request, "file.txt", b"hello".to_vec());
+        assert_eq!(response.body, b"hello");
+    }
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("http-rs-{}-{}", std::process::id(), name));
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::create_dir_all(root.join("public/docs")).unwrap();
+        std::fs::write(root.join("secret.txt"), "top secret").unwrap();
+        std::fs::write(root.join("public/hello.txt"), "hello").unwrap();
+        std::fs::write(root.join("public/docs/guide.html"), "<h1>guide</h1>").unwrap();
+        return root;
+    }
+
+    fn path_request(path: &str) -> HttpRequest {
+        return HttpRequest {
+            path: path.to_string(),
+            ..Default::default()
+        };
+    }
+
+    #[tokio::test]
+    async fn test_serve_dir_serves_files_under_root() {
+        let root = temp_root("serve");
+        let dir = ServeDir::new(root.join("public"));
+
+        let mut response = dir.serve(path_request("/docs/guide.html")).await;
+        assert_eq!(response.body, b"<h1>guide</h1>");
+        let bytes = response.get_bytes();
+        assert!(String::from_utf8_lossy(&bytes).contains("Content-Type: text/html"));
+
+        let response = dir.serve(path_request("/missing.txt")).await;
+        assert!(response.body.starts_with(b"file not found"));
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_serve_dir_rejects_traversal() {
+        let root = temp_root("traversal");
+        let dir = ServeDir::new(root.join("public"));
+
+        for path in [
+            "/../secret.txt",
+            "/docs/../../secret.txt",
+            "/./../secret.txt",
+        ] {
+            let mut response = dir.serve(path_request(path)).await;
+            let bytes = response.get_bytes();
+            assert!(
+                String::from_utf8_lossy(&bytes).starts_with("HTTP/1.1 403 Forbidden\r\n"),
+                "{path}"
+            );
+        }
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_dir_rejects_symlink_escaping_root() {
+        let root = temp_root("symlink");
+        std::os::unix::fs::symlink(root.join("secret.txt"), root.join("public/escape.txt"))
+            .unwrap();
+        std::os::unix::fs::symlink(
+            root.join("public/hello.txt"),
+            root.join("public/inside.txt"),
+        )
+        .unwrap();
+
+        let dir = ServeDir::new(root.join("public"));
+
+        let mut response = dir.serve(path_request("/escape.txt")).await;
+        assert!(!response.body.starts_with(b"top secret"));
+        let bytes = response.get_bytes();
+        assert!(String::from_utf8_lossy(&bytes).starts_with("HTTP/1.1 403 Forbidden\r\n"));
+
+        let response = dir.serve(path_request("/inside.txt")).await;
+        assert_eq!(response.body, b"hello");
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_serve_dir_falls_back_to_index_file() {
+        let root = temp_root("index");
+        std::fs::write(root.join("public/index.html"), "<h1>home</h1>").unwrap();
+        std::fs::write(root.join("public/docs/start.html"), "<h1>start</h1>").unwrap();
+
+        let mut dir = ServeDir::new(root.join("public"));
+        let response = dir.serve(path_request("/")).await;
+        assert_eq!(response.body, b"<h1>home</h1>");
+
+        let response = dir.serve(path_request("/docs/")).await;
+        assert!(response.body.starts_with(b"file not found"));
+
+        dir.set_index_file(Some("start.html"));
+        let response = dir.serve(path_request("/docs/")).await;
+        assert_eq!(response.body, b"<h1>start</h1>");
+
+        dir.set_index_file(None);
+        let response = dir.serve(path_request("/")).await;
+        assert!(response.body.starts_with(b"file not found"));
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_serve_dir_lists_directories_when_enabled() {
+        let root = temp_root("listing");
+        std::fs::write(root.join("public/<b>&.txt"), "tricky").unwrap();
+        std::fs::write(root.join("public/with space.txt"), "spaced").unwrap();
+
+        let mut dir = ServeDir::new(root.join("public"));
+        let response = dir.serve(path_request("/")).await;
+        assert_eq!(response.status_code, 404);
+
+        dir.set_list_directories(true);
+        let response = dir.serve(path_request("/")).await;
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response.get_header("Content-Type"),
+            Some("text/html; charset=UTF-8")
+        );
+
+        let listing = String::from_utf8(response.body).unwrap();
+        assert!(listing.contains(r#"<a href="/hello.txt">hello.txt</a>"#));
+        assert!(listing.contains(r#"<a href="/docs/">docs/</a>"#));
+        assert!(listing.contains(r#"<a href="/with%20space.txt">with space.txt</a>"#));
+        assert!(listing.contains(r#"<a href="/%3Cb%3E%26.txt">&lt;b&gt;&amp;.txt</a>"#));
+        assert!(!listing.contains("<b>"));
+
+        let response = dir.serve(path_request("/docs")).await;
+        let listing = String::from_utf8(response.body).unwrap();
+        assert!(listing.contains(r#"<a href="/docs/guide.html">guide.html</a>"#));
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_serve_dir_honours_if_modified_since() {
+        let root = temp_root("modified");
+        let dir = ServeDir::new(root.join("public"));
+        let modified = std::fs::metadata(root.join("public/hello.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        let last_modified = http_date(modified);
+
+        let conditional = |value: &str| {
+            let mut request = path_request("/hello.txt");
+            request.headers.insert("If-Modified-Since", value);
+            request
+        };
+
+        let response = dir.serve(path_request("/hello.txt")).await;
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            response.get_header("Last-Modified"),
+            Some(last_modified.as_str())
+        );
+
+        let response = dir.serve(conditional(&last_modified)).await;
+        assert_eq!(response.status_code, 304);
+        assert!(response.body.is_empty());
+        assert_eq!(
+            response.get_header("Last-Modified"),
+            Some(last_modified.as_str())
+        );
+
+        let stale = http_date(modified - std::time::Duration::from_secs(3600));
+        let response = dir.serve(conditional(&stale)).await;
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, b"hello");
+
+        let response = dir.serve(conditional("not a date")).await;
+        assert_eq!(response.status_code, 200);
+
+        let mut request = conditional(&last_modified);
+        request.headers.insert("If-None-Match", "\"other\"");
+        let response = dir.serve(request).await;
+        assert_eq!(response.status_code, 200);
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_head_matches_get_headers_without_reading_file() {
+        let root = temp_root("head");
+        let mut router: Router = Router::new(None);
+        router.get("/static/*", serve_dir(root.join("public")));
+
+        let head_of = |mut response: HttpResponse| {
+            let bytes = response.get_bytes();
+            let text = String::from_utf8_lossy(&bytes).to_string();
+            let (head, body) = text.split_once("\r\n\r\n").unwrap();
+            let mut head = head
+                .lines()
+                .filter(|line| !line.starts_with("Date:"))
+                .collect::<Vec<_>>();
+            head.sort();
+            let head = head.join("\r\n");
+            (head, body.to_string())
+        };
+
+        let get = HttpRequest {
+            method: HttpMethod::Get,
+            path: "/static/docs/guide.html".to_string(),
+            ..Default::default()
+        };
+        let head = HttpRequest {
+            method: HttpMethod::Head,
+            ..get.clone()
+        };
+
+        let (get_head, get_body) = head_of(router.fetch(get).await.unwrap());
+        let (head_head, head_body) = head_of(router.fetch(head).await.unwrap());
+
+        assert_eq!(get_body, "<h1>guide</h1>");
+        assert!(head_body.is_empty());
+        assert_eq!(get_head, head_head);
+        assert!(head_head.contains("Content-Length: 14"));
+        assert!(head_head.contains("ETag: "));
+
+        std::fs::remove_dir_all(root).ok();
+    }
+}
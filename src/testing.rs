@@ -0,0 +1,164 @@
+use std::{io, net::SocketAddr};
+
+use tokio::{
+    io::{AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::oneshot,
+};
+
+use crate::{
+    http::{HttpMethod, HttpResponse, ParseOptions, read_response},
+    router::Router,
+    server::Server,
+};
+
+pub struct TestServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl TestServer {
+    pub async fn spawn<T: Send + Sync + 'static>(router: Router<T>) -> io::Result<TestServer> {
+        let bound = Server::new(0, "127.0.0.1").bind().await?;
+        let addr = bound.local_addr()?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            let shutdown = async {
+                shutdown_rx.await.ok();
+            };
+
+            if let Err(e) = bound.serve_with_shutdown(router, shutdown).await {
+                eprintln!("Test server failed: {}", e);
+            }
+        });
+
+        return Ok(TestServer {
+            addr,
+            shutdown: Some(shutdown_tx),
+        });
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        return self.addr;
+    }
+
+    pub async fn request(
+        &self,
+        method: HttpMethod,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> io::Result<HttpResponse> {
+        let mut request = format!("{} {} HTTP/1.1\r\n", method.as_str(), path);
+        request.push_str(&format!("Host: {}\r\n", self.addr));
+        for (key, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", key, value));
+        }
+        if !body.is_empty() {
+            request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        request.push_str("Connection: close\r\n\r\n");
+
+        let mut stream = TcpStream::connect(self.addr).await?;
+        stream.write_all(request.as_bytes()).await?;
+        stream.write_all(body).await?;
+
+        let mut reader = BufReader::new(stream);
+        let options = ParseOptions {
+            max_body_size: usize::MAX,
+            ..Default::default()
+        };
+        return read_response(&mut reader, method == HttpMethod::Head, &options).await;
+    }
+
+    pub async fn get(&self, path: &str) -> io::Result<HttpResponse> {
+        return self.request(HttpMethod::Get, path, &[], &[]).await;
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_test_server_round_trips_requests() {
+        let mut router: Router = Router::new(None);
+        router.post(
+            "/echo/:name",
+            Box::new(|req| {
+                Box::pin(async move {
+                    let name = req.param_str("name").unwrap_or_default().to_owned();
+                    let mut response = HttpResponse::body(req.body, Some("text/plain"));
+                    response.insert_header("X-Name", &name);
+                    response
+                })
+            }),
+        );
+        router.get(
+            "/stream",
+            Box::new(|_req| Box::pin(async move { HttpResponse::chunked(&b"streamed body"[..]) })),
+        );
+
+        let server = TestServer::spawn(router).await.unwrap();
+
+        let response = server
+            .request(
+                HttpMethod::Post,
+                "/echo/ferris",
+                &[("X-Trace", "1")],
+                b"hello",
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.get_header("X-Name"), Some("ferris"));
+        assert_eq!(response.get_header("Content-Type"), Some("text/plain"));
+        assert_eq!(response.body, b"hello");
+
+        let response = server.get("/stream").await.unwrap();
+        assert_eq!(response.body, b"streamed body");
+
+        let response = server.get("/missing").await.unwrap();
+        assert_eq!(response.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_requests_carry_peer_address() {
+        let mut router: Router = Router::new(None);
+        router.get(
+            "/whoami",
+            Box::new(|req| {
+                Box::pin(async move {
+                    let peer = req.peer_addr.map(|addr| addr.ip().to_string());
+                    let client = req.client_ip().map(|ip| ip.to_string());
+                    HttpResponse::text(&format!("{:?} {:?}", peer, client))
+                })
+            }),
+        );
+
+        let server = TestServer::spawn(router).await.unwrap();
+
+        let response = server.get("/whoami").await.unwrap();
+        assert_eq!(response.body, br#"Some("127.0.0.1") Some("127.0.0.1")"#);
+
+        let response = server
+            .request(
+                HttpMethod::Get,
+                "/whoami",
+                &[("X-Forwarded-For", "198.51.100.4")],
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.body, br#"Some("127.0.0.1") Some("198.51.100.4")"#);
+    }
+}